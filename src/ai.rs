@@ -0,0 +1,70 @@
+use std::collections::{HashMap, VecDeque};
+
+use petgraph::{algo::astar, graph::NodeIndex, visit::{EdgeRef, NodeFiltered}};
+
+use super::{GameWorld, Position, points::{Point, Area, Coord::{X, Y}}};
+
+impl GameWorld {
+    /// Whether monster `i` can see the player.
+    pub(super) fn sees_player(&self, i: usize) -> bool {
+        self.map.visible_tiles(self.monsters[i].pos).contains_key(&self.player.pos)
+    }
+
+    /// The next tile for monster `i` on a shortest route to `target`.
+    /// The route is planned over the areas of the map first, around closed
+    /// doors, and then tile by tile through the area the monster is in.
+    pub(super) fn step_towards(
+        &self, i: usize, target: Position, target_area: NodeIndex,
+    ) -> Option<Position> {
+        let monster = &self.monsters[i];
+
+        let middle = |area: NodeIndex| self.map[area].middle();
+        let passable = NodeFiltered::from_fn(&*self.map,
+            |area| area == target_area || self.map[area].is_passable());
+        let (_, route) = astar(&passable, monster.area,
+            |area| area == target_area,
+            |edge| distance(middle(edge.source()), middle(edge.target())),
+            |area| distance(middle(area), target),
+        )?;
+
+        match route.get(1) {
+            Some(&next) => self.first_step(monster.pos, monster.area,
+                |p| self.map[next].contains(p)),
+            None => self.first_step(monster.pos, monster.area, |p| p == target),
+        }
+    }
+
+    /// The first step of a shortest path from `from` through `area` to a
+    /// tile that is a `goal`.
+    fn first_step(
+        &self, from: Position, area: NodeIndex, goal: impl Fn(Position) -> bool,
+    ) -> Option<Position> {
+        let mut came_from = HashMap::from([(from, from)]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(p) = queue.pop_front() {
+            if goal(p) {
+                let mut step = p;
+                while came_from[&step] != from {
+                    step = came_from[&step];
+                }
+                return Some(step);
+            }
+            for next in [p + X(1), p - X(1), p + Y(1), p - Y(1)] {
+                if came_from.contains_key(&next) {
+                    continue;
+                }
+                if self.map[area].contains(next) || goal(next) {
+                    came_from.insert(next, p);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+fn distance(a: Position, b: Position) -> i32 {
+    let Point { x, y } = a - b;
+    x.abs() + y.abs()
+}
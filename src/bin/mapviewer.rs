@@ -0,0 +1,173 @@
+use std::io::{self, Result, Write};
+
+use crossterm::{execute, queue, terminal, cursor::{self, MoveTo},
+    style::Print, event::KeyCode};
+use rogue::{Layout, MIN_SIZE, points::{Space, Position, Move}, render::Terminal};
+
+const CLEAR_ALL: terminal::Clear = terminal::Clear(terminal::ClearType::All);
+
+const MAX_DEPTH: u8 = 8;
+
+/// Steps through the stages of level generation.
+///
+/// Keys:
+/// * Left/Right: previous/next stage
+/// * r: new seed
+/// * +/-: deeper/shallower partitioning
+/// * w/W, h/H: narrower/wider, lower/higher map
+/// * Esc: exit
+fn main() -> Result<()> {
+    let mut out = io::stdout();
+    let mut viewer = Viewer {
+        seed: rand::random(),
+        width: 59,
+        height: 17,
+        depth: 4,
+        stage: Stage::Partitions,
+    };
+
+    terminal::enable_raw_mode()?;
+    execute!(out, cursor::Hide)?;
+
+    loop {
+        viewer.draw(&mut out)?;
+
+        match rogue::util::input()? {
+            KeyCode::Esc => break,
+            KeyCode::Right => viewer.stage = viewer.stage.next(),
+            KeyCode::Left => viewer.stage = viewer.stage.prev(),
+            KeyCode::Char('r') => viewer.seed = rand::random(),
+            KeyCode::Char('+') => viewer.depth = (viewer.depth + 1).min(MAX_DEPTH),
+            KeyCode::Char('-') => viewer.depth = viewer.depth.saturating_sub(1),
+            KeyCode::Char('w') => viewer.width = (viewer.width - 1).max(MIN_SIZE),
+            KeyCode::Char('W') => viewer.width += 1,
+            KeyCode::Char('h') => viewer.height = (viewer.height - 1).max(MIN_SIZE),
+            KeyCode::Char('H') => viewer.height += 1,
+            _ => {}
+        }
+    }
+
+    terminal::disable_raw_mode()?;
+    execute!(out, CLEAR_ALL, MoveTo(0, 0), cursor::Show)
+}
+
+struct Viewer {
+    seed: u64,
+    width: i32,
+    height: i32,
+    depth: u8,
+    stage: Stage,
+} impl Viewer {
+    fn draw(&self, out: &mut impl Write) -> Result<()> {
+        let Self { seed, width, height, depth, stage } = *self;
+        let Layout { partitions, rooms, halls, doors, world } =
+            rogue::generate_layout(width, height, depth, seed);
+
+        queue!(out, CLEAR_ALL)?;
+        if let Stage::Map = stage {
+            world.print_map(&mut Terminal(&mut *out))?;
+        } else {
+            for (i, space) in partitions.iter().enumerate() {
+                let c = char::from_digit(i as u32 % 36, 36).unwrap_or('#');
+                space.draw_tl(out, '+')?;
+                space.pos.downright(out, c)?;
+            }
+            Space::new(1, 1, width, height).draw_br(out, '+')?;
+        }
+        if let Stage::Rooms | Stage::Halls = stage {
+            for room in rooms {
+                draw_points(&room, out, '.')?;
+            }
+        }
+        if let Stage::Halls = stage {
+            draw_points(halls.into_iter().flatten(), out, '░')?;
+            draw_points(doors, out, '∏')?;
+        }
+
+        queue!(out,
+            move_to(0, height + 2),
+            Print(format!("{stage:?} | seed: {seed} | depth: {depth} | size: {width}x{height}")),
+            move_to(0, height + 3),
+            Print("←/→: stage  r: reseed  +/-: depth  w/W h/H: size  Esc: quit"),
+        )?;
+        out.flush()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Stage { Partitions, Rooms, Halls, Map } impl Stage {
+    fn next(self) -> Self {
+        match self {
+            Stage::Partitions => Stage::Rooms,
+            Stage::Rooms => Stage::Halls,
+            Stage::Halls | Stage::Map => Stage::Map,
+        }
+    }
+
+    fn prev(self) -> Self {
+        match self {
+            Stage::Partitions | Stage::Rooms => Stage::Partitions,
+            Stage::Halls => Stage::Rooms,
+            Stage::Map => Stage::Halls,
+        }
+    }
+}
+
+trait Draw {
+    fn draw_tl(self, out: &mut impl Write, c: char) -> Result<()>;
+    fn draw_br(self, out: &mut impl Write, c: char) -> Result<()>;
+}
+
+trait DrawPoint {
+    fn downright(self, out: &mut impl Write, c: char) -> Result<()>;
+}
+
+impl DrawPoint for Position {
+    fn downright(self, out: &mut impl Write, c: char) -> Result<()> {
+        let p = self + Move::RD;
+        queue!(out, move_to(p.x, p.y), Print(c))
+    }
+}
+
+impl Draw for Space {
+    fn draw_tl(self, out: &mut impl Write, c: char) -> Result<()> {
+        let Self { pos, end } = self;
+        queue!(out, move_to(pos.x, pos.y))?;
+        for _ in pos.x..end.x {
+            queue!(out, Print(c))?;
+        }
+        for row in pos.y..end.y {
+            queue!(out, move_to(pos.x, row), Print(c))?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_br(self, out: &mut impl Write, c: char) -> Result<()> {
+        let Self { pos, end } = self;
+        for row in pos.y..end.y {
+            queue!(out, move_to(end.x, row), Print(c))?;
+        }
+        queue!(out, move_to(pos.x, end.y))?;
+        for _ in pos.x..=end.x {
+            queue!(out, Print(c))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn draw_points(
+    points: impl IntoIterator<Item = Position>,
+    out: &mut impl Write, c: char,
+) -> Result<()> {
+    for p in points {
+        queue!(out, move_to(p.x, p.y), Print(c))?;
+    }
+    Ok(())
+}
+
+/// Generated maps never go left of or above the screen.
+fn move_to(x: i32, y: i32) -> MoveTo {
+    MoveTo(x.max(0) as u16, y.max(0) as u16)
+}
@@ -0,0 +1,167 @@
+use serde::{Serialize, Deserialize};
+
+use super::{GameWorld, Player, Next, Change, MonsterKind, Position};
+
+/// Whoever can attack or be attacked.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Actor {
+    Player,
+    Monster(usize),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Stats {
+    pub hp: i32,
+    pub max_hp: i32,
+    pub attack: i32,
+    pub defence: i32,
+} impl Stats {
+    pub fn new(hp: i32, attack: i32, defence: i32) -> Self {
+        Self { hp, max_hp: hp, attack, defence }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.hp <= 0
+    }
+
+    /// Damage dealt by an attack on `target`. Every hit does at least 1.
+    fn damage(&self, target: &Stats) -> i32 {
+        (self.attack - target.defence).max(1)
+    }
+}
+
+/// XP needed for each level, times the level.
+const XP_PER_LEVEL: u32 = 10;
+/// Max hp gained with each level.
+const HP_PER_LEVEL: i32 = 5;
+
+/// The player's attributes, which grow as they gain levels.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Character {
+    /// Adds to attack.
+    pub strength: i32,
+    /// Adds half to defence.
+    pub dexterity: i32,
+    pub level: u32,
+    /// Experience gained towards the next level.
+    pub xp: u32,
+} impl Character {
+    pub fn new(strength: i32, dexterity: i32) -> Self {
+        Self { strength, dexterity, level: 1, xp: 0 }
+    }
+
+    /// XP needed to reach the next level.
+    pub fn next_level(&self) -> u32 {
+        self.level * XP_PER_LEVEL
+    }
+
+    /// `stats` with the bonuses of the attributes added.
+    pub fn apply(&self, mut stats: Stats) -> Stats {
+        stats.attack += self.strength;
+        stats.defence += self.dexterity / 2;
+        stats
+    }
+}
+
+impl MonsterKind {
+    pub fn stats(self) -> Stats {
+        match self {
+            MonsterKind::Rat => Stats::new(3, 2, 0),
+            MonsterKind::Goblin => Stats::new(6, 3, 1),
+            MonsterKind::Zombie => Stats::new(12, 4, 0),
+        }
+    }
+
+    /// XP the player gets for killing the monster.
+    pub fn xp(self) -> u32 {
+        match self {
+            MonsterKind::Rat => 2,
+            MonsterKind::Goblin => 5,
+            MonsterKind::Zombie => 8,
+        }
+    }
+}
+
+impl GameWorld {
+    /// Whether the player has died.
+    pub fn is_over(&self) -> bool {
+        self.player.stats.is_dead()
+    }
+
+    pub(super) fn attack(&self, attacker: Actor, target: Actor) -> Next {
+        let damage = self.stats(attacker).damage(&self.stats(target));
+        Next(Change::Attack(attacker, target, damage))
+    }
+
+    /// Applies an attack. Killed monsters are removed from the map,
+    /// and the player gets their XP.
+    pub(super) fn hit(&mut self, attacker: Actor, target: Actor, damage: i32) {
+        let stats = self.stats_mut(target);
+        stats.hp -= damage;
+        let dead = stats.is_dead();
+
+        match (attacker, target) {
+            (Actor::Monster(i), Actor::Player) => {
+                let kind = self.monsters[i].kind;
+                self.log(format!("The {} {} you.", kind.name(), kind.verb()));
+                if dead {
+                    self.log("You die...".to_string());
+                }
+            }
+            (_, Actor::Monster(i)) if dead => {
+                let monster = self.monsters.remove(i);
+                self.kills += 1;
+                self.log(format!("You kill the {}.", monster.kind.name()));
+                self.gain_xp(monster.kind.xp());
+            }
+            (_, Actor::Monster(i)) => {
+                self.log(format!("You hit the {}.", self.monsters[i].kind.name()));
+            }
+            (_, Actor::Player) => {}
+        }
+    }
+
+    /// Adds XP to the player, levelling up as many times as it reaches.
+    /// Every level raises strength, dexterity and max hp, and heals as much.
+    pub(super) fn gain_xp(&mut self, xp: u32) {
+        self.player.character.xp += xp;
+        loop {
+            let Player { character, stats, .. } = &mut self.player;
+            if character.xp < character.next_level() {
+                break;
+            }
+            character.xp -= character.next_level();
+            character.level += 1;
+            character.strength += 1;
+            character.dexterity += 1;
+            stats.max_hp += HP_PER_LEVEL;
+            stats.hp += HP_PER_LEVEL;
+
+            let level = character.level;
+            self.log(format!("You reach level {level}."));
+        }
+    }
+
+    pub(super) fn monster_at(&self, p: Position) -> Option<usize> {
+        self.monsters.iter().position(|m| m.pos == p)
+    }
+
+    /// The stats of `actor`, with the bonuses of the player's attributes
+    /// and equipment.
+    pub(super) fn stats(&self, actor: Actor) -> Stats {
+        match actor {
+            Actor::Player => {
+                let Player { stats, character, equipment, .. } = &self.player;
+                equipment.apply(character.apply(*stats))
+            }
+            Actor::Monster(i) => self.monsters[i].stats,
+        }
+    }
+
+    fn stats_mut(&mut self, actor: Actor) -> &mut Stats {
+        match actor {
+            Actor::Player => &mut self.player.stats,
+            Actor::Monster(i) => &mut self.monsters[i].stats,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use petgraph::graph::NodeIndex;
+
+use super::{GameWorld, Next, Change, MapElem, Door, ItemKind, Actor,
+    points::Coord::{X, Y}};
+
+impl GameWorld {
+    /// Opens a closed door next to the player, or unlocks a locked one
+    /// with a key from the inventory. Without a key the player is told the
+    /// door is locked, since it looks like any closed door.
+    pub(super) fn open_door(&self) -> Next {
+        if let Some(index) = self.door_next_to_player(Door::Closed) {
+            return Next(Change::Door(index, Door::Open));
+        }
+        if self.door_next_to_player(Door::Locked).is_none() {
+            return Next(Change::Nothing);
+        }
+        match self.player.inventory.iter().position(|kind| *kind == ItemKind::Key) {
+            Some(slot) => Next(Change::Use(slot)),
+            None => Next(Change::Locked),
+        }
+    }
+
+    /// Closes an open door next to the player, unless something is in the way.
+    pub(super) fn close_door(&self) -> Next {
+        match self.door_next_to_player(Door::Open) {
+            Some(index) => Next(Change::Door(index, Door::Closed)),
+            None => Next(Change::Nothing),
+        }
+    }
+
+    /// Looks for secret doors next to the player. Takes a turn even if
+    /// nothing is found.
+    pub(super) fn search(&self) -> Next {
+        match self.door_next_to_player(Door::Secret) {
+            Some(index) => Next(Change::Door(index, Door::Closed)),
+            None => Next(Change::Wait(Actor::Player)),
+        }
+    }
+
+    /// The first door in `state` next to the player with nothing in it.
+    pub(super) fn door_next_to_player(&self, state: Door) -> Option<NodeIndex> {
+        let pos = self.player.pos;
+        [pos + X(1), pos - X(1), pos + Y(1), pos - Y(1)].into_iter()
+            .filter(|p| !self.occupied(*p) && self.item_at(*p).is_none())
+            .filter_map(|p| self.map.areas.get(&p).copied())
+            .find(|index| matches!(self.map[*index], MapElem::Door(_, door) if door == state))
+    }
+
+    /// Changes the state of a door and tells the player.
+    pub(super) fn set_door(&mut self, index: NodeIndex, state: Door) {
+        let found = matches!(self.map[index], MapElem::Door(_, Door::Secret));
+        self.map.set_door(index, state);
+        self.log(match state {
+            _ if found => "You find a secret door.".to_string(),
+            Door::Open => "You open the door.".to_string(),
+            _ => "You close the door.".to_string(),
+        });
+    }
+}
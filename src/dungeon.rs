@@ -0,0 +1,92 @@
+use std::{collections::HashMap, mem};
+
+use petgraph::graph::NodeIndex;
+use serde::{Serialize, Deserialize};
+
+use super::{GameWorld, Next, Change, Map, Monster, TileMap, Stairs, Position,
+    item::Item, map};
+
+/// What every level of a dungeon is generated from.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub(super) struct Dungeon {
+    pub seed: u64,
+    pub width: i32,
+    pub height: i32,
+    pub max_partition: u8,
+} impl Dungeon {
+    /// The seed of the level at `depth`. The first level uses the dungeon seed.
+    pub fn level_seed(&self, depth: u32) -> u64 {
+        self.seed ^ u64::from(depth - 1).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    }
+}
+
+/// Everything on a level that stays there while the player is elsewhere.
+#[derive(Serialize, Deserialize)]
+pub(super) struct Level {
+    pub map: Map,
+    pub monsters: Vec<Monster>,
+    pub items: Vec<Item>,
+    pub explored: TileMap,
+} impl Level {
+    pub fn new(map: Map, monsters: Vec<Monster>, items: Vec<Item>) -> Self {
+        Self { map, monsters, items, explored: TileMap(HashMap::new()) }
+    }
+}
+
+impl GameWorld {
+    /// How far down the dungeon the player is, starting at 1.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Takes the stairs the player is standing on, if they lead `stairs`.
+    pub(super) fn take_stairs(&self, stairs: Stairs) -> Next {
+        match self.map.stairs.get(&self.player.pos) {
+            Some(s) if *s == stairs => Next(Change::Stairs(stairs)),
+            _ => Next(Change::Nothing),
+        }
+    }
+
+    /// Leaves the current level by `stairs`, and puts the player on the
+    /// other end of them. Levels are kept as they were left, and generated
+    /// the first time they are visited.
+    pub(super) fn change_level(&mut self, stairs: Stairs) {
+        let (depth, arrival) = match stairs {
+            Stairs::Down => (self.depth + 1, Stairs::Up),
+            Stairs::Up => (self.depth - 1, Stairs::Down),
+        };
+        let level = self.levels.remove(&depth)
+            .unwrap_or_else(|| map::generate_level(&self.dungeon, depth));
+
+        let left = Level {
+            map: mem::replace(&mut self.map, level.map),
+            monsters: mem::replace(&mut self.monsters, level.monsters),
+            items: mem::replace(&mut self.items, level.items),
+            explored: mem::replace(&mut self.explored, level.explored),
+        };
+        self.levels.insert(self.depth, left);
+        self.depth = depth;
+
+        let stairs = self.map.stairs.iter()
+            .find(|(_, s)| **s == arrival)
+            .map(|(pos, _)| *pos)
+            .expect("levels have stairs both ways");
+        let area = self.map.areas[&stairs];
+        self.player.pos = self.free_tile(stairs, area);
+        self.player.area = area;
+
+        self.log(match arrival {
+            Stairs::Up => format!("You go down to depth {depth}."),
+            Stairs::Down => format!("You go up to depth {depth}."),
+        });
+    }
+
+    /// The tile in `area` closest to `p` without a monster on it.
+    fn free_tile(&self, p: Position, area: NodeIndex) -> Position {
+        let mut tiles = self.map[area].points();
+        tiles.sort_by_key(|t| (t.x - p.x).abs() + (t.y - p.y).abs());
+        tiles.into_iter()
+            .find(|t| self.monster_at(*t).is_none())
+            .unwrap_or(p)
+    }
+}
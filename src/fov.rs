@@ -13,7 +13,7 @@ type Relative = Position;
     y + - - - -
       x 0 1 2 3
 */
-type Slope = Ratio<u16>;
+type Slope = Ratio<i32>;
 
 /// Shadowcasting FOV function.
 pub fn compute(
@@ -87,7 +87,7 @@ enum OctSection {
 
 /// Represents a row in an octant.
 struct Col {
-    depth: u16,
+    depth: i32,
     top_slope: Slope,
     bottom_slope: Slope,
 } impl Col {
@@ -127,13 +127,13 @@ fn slope(p: Relative) -> Slope {
     Ratio::new(2 * p.y - 1, 2 * p.x)
 }
 
-const HALF: Ratio<u16> = Ratio::new_raw(1, 2);
+const HALF: Ratio<i32> = Ratio::new_raw(1, 2);
 
-fn round_half_down(n: Ratio<u16>) -> u16 {
+fn round_half_down(n: Ratio<i32>) -> i32 {
     (n - HALF).ceil().to_integer()
 }
 
-fn round_half_up(n: Ratio<u16>) -> u16 {
+fn round_half_up(n: Ratio<i32>) -> i32 {
     (n + HALF).floor().to_integer()
 }
 
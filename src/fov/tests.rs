@@ -71,3 +71,16 @@ fn rows() {
     assert_eq!(Some(Point { x: 2, y: 2 }), tiles.next());
     assert_eq!(None, tiles.next());
 }
+
+#[test]
+fn origin_at_zero() {
+    let origin = Point { x: 0, y: 0 };
+    let is_floor = |p: Position| p.x.abs() < 3 && p.y.abs() < 3;
+    let mut visible = Vec::new();
+
+    compute(origin, is_floor, |p| visible.push(p));
+
+    assert!(visible.contains(&Point { x: -2, y: -2 }));
+    assert!(visible.contains(&Point { x: -3, y: 0 }));
+    assert!(!visible.contains(&Point { x: -4, y: 0 }));
+}
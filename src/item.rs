@@ -0,0 +1,237 @@
+use crossterm::{event::KeyCode, style::Color};
+use rand::prelude::*;
+use serde::{Serialize, Deserialize};
+use strum_macros::EnumIter;
+
+use super::{GameWorld, Next, Change, Position, MapElem, Door, combat::Stats};
+
+/// How many items the player can carry.
+pub const INVENTORY_SIZE: usize = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, EnumIter, Serialize, Deserialize)]
+pub enum ItemKind {
+    Dagger,
+    Sword,
+    LeatherArmour,
+    ChainMail,
+    HealingPotion,
+    TeleportScroll,
+    MappingScroll,
+    /// Only generated together with a locked door.
+    Key,
+} impl ItemKind {
+    /// What the item is called, looks like and does.
+    pub fn def(self) -> ItemDef {
+        use {ItemKind::*, Effect::*, Slot::*};
+        let (name, glyph, colour, effect) = match self {
+            Dagger => ("dagger", '/', Color::Grey, Equip { slot: Weapon, attack: 1, defence: 0 }),
+            Sword => ("sword", '/', Color::Cyan, Equip { slot: Weapon, attack: 3, defence: 0 }),
+            LeatherArmour => ("leather armour", '[', Color::DarkYellow,
+                Equip { slot: Armour, attack: 0, defence: 1 }),
+            ChainMail => ("chain mail", '[', Color::Cyan,
+                Equip { slot: Armour, attack: 0, defence: 3 }),
+            HealingPotion => ("healing potion", '!', Color::Red, Heal(8)),
+            TeleportScroll => ("teleport scroll", '?', Color::Magenta, Teleport),
+            MappingScroll => ("mapping scroll", '?', Color::Yellow, Reveal),
+            Key => ("key", '-', Color::Yellow, Unlock),
+        };
+        ItemDef { name, glyph, colour, effect }
+    }
+
+    pub fn name(self) -> &'static str {
+        self.def().name
+    }
+}
+
+pub struct ItemDef {
+    pub name: &'static str,
+    pub glyph: char,
+    pub colour: Color,
+    pub effect: Effect,
+}
+
+/// What happens when the player uses an item.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Effect {
+    /// Goes in an equipment slot, adding to the player's stats while worn.
+    Equip { slot: Slot, attack: i32, defence: i32 },
+    /// Restores hit points, up to the maximum.
+    Heal(i32),
+    /// Moves the player to a random spot in a random room.
+    Teleport,
+    /// Shows the player the whole level.
+    Reveal,
+    /// Opens a locked door next to the player, and is used up.
+    Unlock,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Slot {
+    Weapon,
+    Armour,
+}
+
+/// The items the player is wielding and wearing.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub(super) struct Equipment {
+    pub weapon: Option<ItemKind>,
+    pub armour: Option<ItemKind>,
+} impl Equipment {
+    fn slot_mut(&mut self, slot: Slot) -> &mut Option<ItemKind> {
+        match slot {
+            Slot::Weapon => &mut self.weapon,
+            Slot::Armour => &mut self.armour,
+        }
+    }
+
+    /// `stats` with the bonuses of the equipment added.
+    pub fn apply(&self, mut stats: Stats) -> Stats {
+        for kind in [self.weapon, self.armour].into_iter().flatten() {
+            if let Effect::Equip { attack, defence, .. } = kind.def().effect {
+                stats.attack += attack;
+                stats.defence += defence;
+            }
+        }
+        stats
+    }
+}
+
+/// An item lying on the floor.
+#[derive(Serialize, Deserialize)]
+pub(super) struct Item {
+    pub kind: ItemKind,
+    pub pos: Position,
+}
+
+impl GameWorld {
+    /// The items the player carries.
+    pub fn inventory(&self) -> &[ItemKind] {
+        &self.player.inventory
+    }
+
+    /// The items the player is wielding and wearing.
+    pub fn equipment(&self) -> [(Slot, Option<ItemKind>); 2] {
+        let Equipment { weapon, armour } = self.player.equipment;
+        [(Slot::Weapon, weapon), (Slot::Armour, armour)]
+    }
+
+    /// Picks up the item the player is standing on, if there is room for it.
+    pub(super) fn pick_up(&self) -> Next {
+        if self.player.inventory.len() >= INVENTORY_SIZE {
+            return Next(Change::Nothing);
+        }
+        match self.item_at(self.player.pos) {
+            Some(i) => Next(Change::PickUp(i)),
+            None => Next(Change::Nothing),
+        }
+    }
+
+    /// Drops the inventory item chosen by `key`, where 'a' is the first item.
+    pub fn drop_item(&self, key: KeyCode) -> Next {
+        match inventory_slot(key) {
+            Some(slot) if slot < self.player.inventory.len() => Next(Change::Drop(slot)),
+            _ => Next(Change::Nothing),
+        }
+    }
+
+    /// Uses the inventory item chosen by `key`, where 'a' is the first item.
+    pub fn use_item(&self, key: KeyCode) -> Next {
+        match inventory_slot(key) {
+            Some(slot) if slot < self.player.inventory.len() => Next(Change::Use(slot)),
+            _ => Next(Change::Nothing),
+        }
+    }
+
+    /// The item on top at `p`, if there are any.
+    pub(super) fn item_at(&self, p: Position) -> Option<usize> {
+        self.items.iter().rposition(|item| item.pos == p)
+    }
+
+    pub(super) fn take(&mut self, i: usize) {
+        let item = self.items.remove(i);
+        self.player.inventory.push(item.kind);
+        self.log(format!("You pick up the {}.", item.kind.name()));
+    }
+
+    pub(super) fn put_down(&mut self, slot: usize) {
+        let kind = self.player.inventory.remove(slot);
+        self.items.push(Item { kind, pos: self.player.pos });
+        self.log(format!("You drop the {}.", kind.name()));
+    }
+
+    /// Applies the effect of the item in inventory `slot`.
+    /// Equipment swaps places with what was in its equipment slot.
+    pub(super) fn apply(&mut self, slot: usize) {
+        let kind = self.player.inventory.remove(slot);
+        let name = kind.name();
+        let message = match kind.def().effect {
+            Effect::Equip { slot, .. } => {
+                if let Some(old) = self.player.equipment.slot_mut(slot).replace(kind) {
+                    self.player.inventory.push(old);
+                }
+                match slot {
+                    Slot::Weapon => format!("You wield the {name}."),
+                    Slot::Armour => format!("You put on the {name}."),
+                }
+            }
+            Effect::Heal(hp) => {
+                let stats = &mut self.player.stats;
+                stats.hp = (stats.hp + hp).min(stats.max_hp);
+                format!("You drink the {name}. You feel better.")
+            }
+            Effect::Teleport => {
+                self.teleport();
+                format!("You read the {name}. You are somewhere else.")
+            }
+            Effect::Reveal => {
+                for (pos, tile) in self.map.all_tiles().iter() {
+                    self.explored.insert(*pos, *tile);
+                }
+                format!("You read the {name}. You see the whole level.")
+            }
+            Effect::Unlock => match self.door_next_to_player(Door::Locked) {
+                Some(index) => {
+                    self.map.set_door(index, Door::Open);
+                    format!("You unlock the door with the {name}.")
+                }
+                None => {
+                    self.player.inventory.insert(slot, kind);
+                    format!("There is no lock here for the {name}.")
+                }
+            },
+        };
+        self.log(message);
+    }
+
+    /// Moves the player to a free tile in a random room they could walk to,
+    /// so they can't land on the far side of a locked door.
+    fn teleport(&mut self) {
+        let reachable = self.map.reachable(self.player.area, |area| {
+            !matches!(area, MapElem::Door(_, Door::Locked))
+        });
+        let rooms: Vec<_> = self.map.node_indices()
+            .filter(|i| reachable.contains(i) && matches!(self.map[*i], MapElem::Room(_)))
+            .collect();
+        for _ in 0..100 {
+            let Some(&area) = rooms.choose(&mut self.rng) else { return };
+            let Some(&pos) = self.map[area].points().choose(&mut self.rng) else { continue };
+            if !self.occupied(pos) {
+                self.player.pos = pos;
+                self.player.area = area;
+                return;
+            }
+        }
+    }
+}
+
+/// The letter an inventory slot is listed under.
+pub(super) fn slot_letter(slot: usize) -> char {
+    (b'a' + slot as u8) as char
+}
+
+fn inventory_slot(key: KeyCode) -> Option<usize> {
+    match key {
+        KeyCode::Char(c @ 'a'..='z') => Some(c as usize - 'a' as usize),
+        _ => None,
+    }
+}
@@ -1,44 +1,144 @@
 pub mod write;
+pub mod render;
+pub mod menu;
+mod ai;
+mod combat;
+mod door;
+mod dungeon;
 mod fov;
+mod item;
+mod log;
 mod map;
-mod points;
+mod save;
+mod turn;
+pub mod points;
 pub mod util;
 
-use std::collections::HashMap;
+use std::{collections::{HashMap, HashSet}, ops::Range};
 
 use crossterm::event::KeyCode;
-use petgraph::{Graph, Undirected, graph::NodeIndex};
+use petgraph::{Graph, Undirected, graph::{NodeIndex, EdgeIndex}, visit::{Dfs, NodeFiltered}};
+use rand::prelude::*;
+use rand_chacha::ChaCha12Rng;
+use serde::{Serialize, Deserialize};
 
 use points::{ Point, Position, Coord::{X, Y, self}, Coordinate,
-    Rect, Space, Line, Straight, Area,
+    Rect, Line, Path, Area, Move, shape::Shape,
 };
-use strum_macros::EnumIs;
+use strum_macros::{EnumIs, EnumIter};
+use write::Viewport;
+use combat::{Stats, Character};
+use item::{Item, Equipment};
+use dungeon::{Dungeon, Level};
 
+pub use map::{generate_world, generate_layout, Layout, MIN_SIZE};
+pub use combat::Actor;
+pub use item::{ItemKind, ItemDef, Effect, Slot, INVENTORY_SIZE};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Serialize, Deserialize)]
 pub struct GameWorld {
+    /// The level the player is on.
     map: Map,
     player: Player,
+    monsters: Vec<Monster>,
+    /// Items lying on the floor, the last one on top.
+    items: Vec<Item>,
+    /// Tiles the player has seen on this level.
+    explored: TileMap,
+    /// The other levels visited, by depth.
+    levels: HashMap<u32, Level>,
+    dungeon: Dungeon,
+    /// Laid out again by `resize` after loading.
+    #[serde(skip)]
+    view: Viewport,
+    rng: ChaCha12Rng,
+    /// Turns the player has taken.
+    turns: u32,
+    kills: u32,
+    /// How far down the dungeon the player is, starting at 1.
+    depth: u32,
+    messages: Vec<String>,
 } impl GameWorld {
+    fn new(level: Level, player: Player, dungeon: Dungeon, seed: u64) -> Self {
+        let Level { map, monsters, items, explored } = level;
+        let view = Viewport::default().follow(player.pos);
+        let mut world = Self {
+            map, player, monsters, items, explored, dungeon, view,
+            levels: HashMap::new(),
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            turns: 0,
+            kills: 0,
+            depth: 1,
+            messages: Vec::new(),
+        };
+        world.explore();
+        world
+    }
+
     pub fn next(&self, key: KeyCode) -> Next {
         let p = match key {
             KeyCode::Left => self.player.pos - X(1),
             KeyCode::Right => self.player.pos + X(1),
             KeyCode::Up => self.player.pos - Y(1),
             KeyCode::Down => self.player.pos + Y(1),
+            KeyCode::Char('.') => return Next(Change::Wait(Actor::Player)),
+            KeyCode::Char('g') => return self.pick_up(),
+            KeyCode::Char('>') => return self.take_stairs(Stairs::Down),
+            KeyCode::Char('<') => return self.take_stairs(Stairs::Up),
+            KeyCode::Char('o') => return self.open_door(),
+            KeyCode::Char('c') => return self.close_door(),
+            KeyCode::Char('s') => return self.search(),
             _ => return Next(Change::Nothing)
         };
 
-        if self.map[self.player.area].contains(p) {
-            return Next(Change::Pos(p));
+        if let Some(i) = self.monster_at(p) {
+            return self.attack(Actor::Player, Actor::Monster(i));
         }
-        for i in self.map.neighbors(self.player.area) {
-            if self.map[i].contains(p) {
-                return Next(Change::Area(p, i));
-            }
+        match self.step(self.player.area, p) {
+            Some(area) if area == self.player.area => Next(Change::Pos(p)),
+            Some(area) => Next(Change::Area(p, area)),
+            None => Next(Change::Nothing),
+        }
+    }
+
+    /// The indices of the monsters.
+    pub fn monsters(&self) -> Range<usize> {
+        0..self.monsters.len()
+    }
+
+    /// Lets monster `i` chase the player if it can see them, and take a step
+    /// in a random direction otherwise. Attacks the player if they are in
+    /// the way, and waits if it can't move.
+    pub fn next_monster(&mut self, i: usize) -> Next {
+        let Monster { pos, area, .. } = self.monsters[i];
+        let chase = self.sees_player(i)
+            .then(|| self.step_towards(i, self.player.pos, self.player.area))
+            .flatten();
+        let p = match chase {
+            Some(p) => p,
+            None => pos + *[X(1), X(-1), Y(1), Y(-1)].choose(&mut self.rng).unwrap(),
+        };
+
+        if p == self.player.pos {
+            return self.attack(Actor::Monster(i), Actor::Player);
+        }
+        if self.occupied(p) {
+            return Next(Change::Wait(Actor::Monster(i)));
+        }
+        match self.step(area, p) {
+            Some(area) => Next(Change::Monster(i, p, area)),
+            None => Next(Change::Wait(Actor::Monster(i))),
         }
-        Next(Change::Nothing)
     }
 
-    pub fn update(&mut self, Next(change): Next) {
+    pub fn update(&mut self, next @ Next(change): Next) {
+        if next.takes_turn() {
+            self.turns += 1;
+        }
+        self.spend(next);
         match change {
             Change::Pos(pos) => {
                 self.player.pos = pos;
@@ -47,19 +147,142 @@ pub struct GameWorld {
                 self.player.pos = pos;
                 self.player.area = area;
             }
-            _ => {}
+            Change::Monster(i, pos, area) => {
+                let monster = &mut self.monsters[i];
+                monster.pos = pos;
+                monster.area = area;
+                return;
+            }
+            Change::Attack(attacker, target, damage) => {
+                self.hit(attacker, target, damage);
+                return;
+            }
+            Change::PickUp(i) => {
+                self.take(i);
+                return;
+            }
+            Change::Drop(slot) => {
+                self.put_down(slot);
+                return;
+            }
+            Change::Use(slot) => {
+                self.apply(slot);
+            }
+            Change::Stairs(stairs) => {
+                self.change_level(stairs);
+            }
+            Change::Door(index, door) => {
+                self.set_door(index, door);
+            }
+            Change::Locked => {
+                self.log("The door is locked.".to_string());
+                return;
+            }
+            _ => return,
+        }
+        self.view = self.view.follow(self.player.pos);
+        self.explore();
+    }
+
+    /// The area a step from `area` onto `p` leads to, if any.
+    /// Closed doors are in the way.
+    fn step(&self, area: NodeIndex, p: Position) -> Option<NodeIndex> {
+        if self.map[area].contains(p) {
+            return Some(area);
+        }
+        self.map.neighbors(area)
+            .find(|i| self.map[*i].contains(p))
+            .filter(|i| self.map[*i].is_passable())
+    }
+
+    /// The tile at `p` with the item lying there, not counting actors.
+    fn floor_tile(&self, p: Position) -> Option<TileKind> {
+        match self.item_at(p) {
+            Some(i) => Some(TileKind::Obj(ObjKind::Item(self.items[i].kind))),
+            None => self.map.get_tile(p),
+        }
+    }
+
+    fn occupied(&self, p: Position) -> bool {
+        self.player.pos == p || self.monsters.iter().any(|m| m.pos == p)
+    }
+
+    /// The tiles visible from `p`, with the items and monsters on them.
+    fn visible_tiles(&self, p: Position) -> TileMap {
+        let mut tiles = self.map.visible_tiles(p);
+        for item in &self.items {
+            if let Some(tile) = tiles.get_mut(&item.pos).filter(|t| !t.is_obj()) {
+                *tile = TileKind::Obj(ObjKind::Item(item.kind));
+            }
+        }
+        for monster in &self.monsters {
+            if let Some(tile) = tiles.get_mut(&monster.pos) {
+                *tile = TileKind::Obj(ObjKind::Monster(monster.kind));
+            }
+        }
+        tiles
+    }
+
+    /// Remembers the tiles the player can see.
+    fn explore(&mut self) {
+        for pos in self.map.visible_tiles(self.player.pos).keys() {
+            if let Some(tile) = self.map.get_tile(*pos) {
+                self.explored.insert(*pos, tile);
+            }
         }
     }
 }
 
+#[derive(Serialize, Deserialize)]
 struct Player {
     pos: Position,
     area: NodeIndex,
-    //Health, inventory, state, etc.
+    /// Hit points. Attack and defence come from `character` and `equipment`.
+    stats: Stats,
+    character: Character,
+    /// The player acts when this reaches `turn::ACTION_COST`.
+    energy: i32,
+    /// At most `INVENTORY_SIZE` items.
+    inventory: Vec<ItemKind>,
+    equipment: Equipment,
+} impl Player {
+    fn new(pos: Position, area: NodeIndex) -> Self {
+        Self {
+            pos, area,
+            stats: Stats::new(20, 0, 0),
+            character: Character::new(4, 2),
+            energy: turn::ACTION_COST,
+            inventory: Vec::new(),
+            equipment: Equipment::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Monster {
+    kind: MonsterKind,
+    pos: Position,
+    area: NodeIndex,
+    stats: Stats,
+    /// The monster acts when this reaches `turn::ACTION_COST`.
+    energy: i32,
+} impl Monster {
+    fn new(kind: MonsterKind, pos: Position, area: NodeIndex) -> Self {
+        Self { kind, pos, area, stats: kind.stats(), energy: 0 }
+    }
 }
 
 #[derive(Clone, Copy, Default)]
-pub struct Next(Change);
+pub struct Next(Change); impl Next {
+    /// Whether the player did something, so the monsters get to act.
+    pub fn takes_turn(&self) -> bool {
+        matches!(self.0,
+            Change::Pos(..) | Change::Area(..) |
+            Change::Attack(Actor::Player, ..) | Change::Wait(Actor::Player) |
+            Change::PickUp(_) | Change::Drop(_) | Change::Use(_) | Change::Stairs(_) |
+            Change::Door(..))
+    }
+}
 #[derive(Clone, Copy, Default)]
 enum Change {
     Nothing,
@@ -67,9 +290,88 @@ enum Change {
     Init,
     Pos(Position),
     Area(Position, NodeIndex),
+    Monster(usize, Position, NodeIndex),
+    /// An attacker hits a target for an amount of damage.
+    Attack(Actor, Actor, i32),
+    /// An actor does nothing for a turn.
+    Wait(Actor),
+    /// The player picks up an item from the floor.
+    PickUp(usize),
+    /// The player drops an item from an inventory slot.
+    Drop(usize),
+    /// The player uses the item in an inventory slot.
+    Use(usize),
+    /// The player takes the stairs up or down.
+    Stairs(Stairs),
+    /// The player opens or closes the door of an area.
+    Door(NodeIndex, Door),
+    /// The player tries to open a locked door without a key.
+    Locked,
 }
 
-struct Map(Graph<MapElem, (), Undirected>); impl Map {
+/// Saved as the graph and stairs, the lookups are built again. See `save`.
+#[derive(Deserialize)]
+#[serde(from = "save::SavedMap")]
+struct Map {
+    graph: Graph<MapElem, (), Undirected>,
+    /// The area each floor tile belongs to.
+    areas: HashMap<Position, NodeIndex>,
+    /// The walls around the rooms.
+    walls: HashMap<Position, Dir>,
+    /// The stairs to the levels above and below, placed in rooms.
+    stairs: HashMap<Position, Stairs>,
+} impl Map {
+    fn new() -> Self {
+        Self {
+            graph: Graph::new_undirected(),
+            areas: HashMap::new(),
+            walls: HashMap::new(),
+            stairs: HashMap::new(),
+        }
+    }
+
+    fn add_node(&mut self, area: MapElem) -> NodeIndex {
+        let index = self.graph.add_node(area);
+        self.index_tiles(index);
+        index
+    }
+
+    fn add_edge(&mut self, a: NodeIndex, b: NodeIndex, weight: ()) -> EdgeIndex {
+        self.graph.add_edge(a, b, weight)
+    }
+
+    /// Opens, closes or locks the door of an area.
+    fn set_door(&mut self, index: NodeIndex, state: Door) {
+        if let MapElem::Door(_, door) = &mut self.graph[index] {
+            *door = state;
+        }
+    }
+
+    fn extend_with_edges(&mut self, edges: impl IntoIterator<Item = (NodeIndex, NodeIndex)>) {
+        self.graph.extend_with_edges(edges);
+    }
+
+    /// Adds the tiles of an area to the tile lookups.
+    /// Tiles already belonging to an area are left as they are.
+    fn index_tiles(&mut self, index: NodeIndex) {
+        for pos in self.graph[index].points() {
+            self.areas.entry(pos).or_insert(index);
+        }
+
+        if let MapElem::Room(room) = &self.graph[index] {
+            let tiles: HashSet<_> = room.into_iter().collect();
+            let walls: HashSet<_> = tiles.iter()
+                .flat_map(|&p| Move::movers().map(move |m| m(p)))
+                .filter(|p| !tiles.contains(p))
+                .collect();
+
+            for &wall in &walls {
+                let sides = [wall - X(1), wall + X(1), wall - Y(1), wall + Y(1)];
+                self.walls.insert(wall, Dir::joining(sides.map(|p| walls.contains(&p))));
+            }
+        }
+    }
+
     fn visible_tiles(&self, p: Position) -> TileMap {
         let mut tiles = TileMap(HashMap::new());
 
@@ -94,76 +396,77 @@ struct Map(Graph<MapElem, (), Undirected>); impl Map {
     }
 
     fn get_tile(&self, p: Position) -> Option<TileKind> {
-        if let res @ Some(_) = self.contains_tile(p) {
-            return res;
-        }
-        for (wall, dir) in self.walls() {
-            if p == wall {
-                return Some(TileKind::Wall(dir));
-            }
-        }
-        None
-    }
-
-    fn contains_tile(&self, p: Position) -> Option<TileKind> { use TileKind::*;
-        for area in self.node_weights() {
-            if area.contains(p) {
-                return match area {
-                    MapElem::Room(_) => Some(Room),
-                    MapElem::Hall(hall) => Some(Hall(hall.end.into())),
-                    MapElem::Door(_) => Some(Door),
-                    MapElem::Void => None,
-                };
-            }
+        self.stairs.get(&p).map(|stairs| TileKind::Stairs(*stairs))
+            .or_else(|| self.contains_tile(p))
+            .or_else(|| self.walls.get(&p).map(|dir| TileKind::Wall(*dir)))
+    }
+
+    fn contains_tile(&self, p: Position) -> Option<TileKind> {
+        self.areas.get(&p).and_then(|index| self.area_tile(*index, p))
+    }
+
+    /// The tile at `p` in area `index`. A hall tile runs between the sides
+    /// where the hall goes on, or meets the doors and halls it leads to,
+    /// so halls bend at their corners.
+    fn area_tile(&self, index: NodeIndex, p: Position) -> Option<TileKind> {
+        let tile = self[index].tile()?;
+        if !tile.is_hall() {
+            return Some(tile);
         }
-        None
+        let joins = |q| self.areas.get(&q)
+            .is_some_and(|i| *i == index || self.contains_edge(index, *i));
+        let sides = [p - X(1), p + X(1), p - Y(1), p + Y(1)];
+        Some(TileKind::Hall(Dir::joining(sides.map(joins))))
     }
 
-    fn walls(&self) -> impl Iterator<Item = (Position, Dir)> {
-        let mut points = HashMap::new();
+    /// The areas that can be reached from `from` through areas that are
+    /// `passable`.
+    fn reachable(
+        &self, from: NodeIndex, passable: impl Fn(&MapElem) -> bool,
+    ) -> HashSet<NodeIndex> {
+        let graph = NodeFiltered::from_fn(&self.graph, |i| i == from || passable(&self[i]));
+        let mut dfs = Dfs::new(&graph, from);
+        let mut areas = HashSet::new();
+        while let Some(i) = dfs.next(&graph) {
+            areas.insert(i);
+        }
+        areas
+    }
 
-        for room in self.rooms() {
-            let (left, right, top, bottom) = perimeter(*room);
+    fn walls(&self) -> impl Iterator<Item = (Position, Dir)> + '_ {
+        self.walls.iter().map(|(pos, dir)| (*pos, *dir))
+    }
 
-            points.insert(Point { x: left, y: top }, Dir::UL);
-            points.insert(Point { x: right, y: top }, Dir::UR);
-            points.insert(Point { x: left, y: bottom }, Dir::DL);
-            points.insert(Point { x: right, y: bottom }, Dir::DR);
+    fn all_tiles(&self) -> TileMap {
+        let mut tiles = TileMap(self.walls()
+            .map(|(pos, dir)| (pos, TileKind::Wall(dir)))
+            .collect());
 
-            for x in room.pos.x..right {
-                points.insert(Point { x, y: top }, Dir::Hor);
-                points.insert(Point { x, y: bottom }, Dir::Hor);
-            }
-            for y in room.pos.y..bottom {
-                points.insert(Point { x: left, y }, Dir::Ver);
-                points.insert(Point { x: right, y }, Dir::Ver);
-            }
-            
-            fn perimeter(room: Space) -> (u16, u16, u16, u16) {
-                (room.pos.x - 1, room.end.x, room.pos.y - 1, room.end.y)
+        for (pos, index) in &self.areas {
+            if let Some(tile) = self.area_tile(*index, *pos) {
+                tiles.insert(*pos, tile);
             }
         }
-        points.into_iter()
-    }
-
-    fn rooms(&self) -> impl Iterator<Item = &Space> {
-        self.node_weights().filter_map(|area| match area {
-            MapElem::Room(room) => Some(room),
-            _ => None,
-        })
+        for (pos, stairs) in &self.stairs {
+            tiles.insert(*pos, TileKind::Stairs(*stairs));
+        }
+        tiles
     }
 }
 
+/// Saved as a list of tiles. See `save`.
+#[derive(Deserialize)]
+#[serde(from = "Vec<(Position, TileKind)>")]
 struct TileMap(HashMap<Position, TileKind>); impl TileMap {
     fn tiles(&self) -> impl Iterator<Item = Tile> + '_ {
         self.iter().map(|t| t.into())
     }
 
-    fn difference<'a>(&'a self, other: &'a TileMap) -> Difference {
+    fn difference<'a>(&'a self, other: &'a TileMap) -> Difference<'a> {
         Difference { iter: self.iter(), other  }
     }
 
-    fn difference_player<'a>(&'a self, other: &'a TileMap) -> DifferenceKind {
+    fn difference_player<'a>(&'a self, other: &'a TileMap) -> DifferenceKind<'a> {
         DifferenceKind { iter: self.iter(), other  }
     }
 }
@@ -171,44 +474,111 @@ struct Tile {
     pos: Position,
     kind: TileKind,
 }
-#[derive(Clone, Copy, PartialEq, EnumIs)]
+#[derive(Clone, Copy, PartialEq, EnumIs, Serialize, Deserialize)]
 enum TileKind {
     Obj(ObjKind),
-    Door,
+    Door(Door),
     Room,
     Hall(Dir),
     Wall(Dir),
+    Stairs(Stairs),
 } impl TileKind {
     fn is_clear(&self) -> bool {
-        self.is_room() || self.is_hall() || self.is_obj()
+        self.is_room() || self.is_hall() || self.is_obj() || self.is_stairs() ||
+            *self == TileKind::Door(Door::Open)
     }
 }
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 enum ObjKind {
     Player,
+    Monster(MonsterKind),
+    Item(ItemKind),
 }
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, EnumIter, Serialize, Deserialize)]
+enum MonsterKind {
+    Rat,
+    Goblin,
+    Zombie,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Stairs {
+    Up,
+    Down,
+}
+/// Only open doors let actors and sight through.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Door {
+    Open,
+    Closed,
+    /// Opened with a key.
+    Locked,
+    /// Looks like the wall around it until the player searches next to it.
+    Secret,
+}
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 enum Dir { //None,
     //Up, Down, Left, Right,
     Hor, Ver, UL, UR, DL, DR,
     //UHor, DHor, VerL, VerR,
     //All,
+} impl Dir {
+    /// The way a line runs that goes on to the left, right, up and down
+    /// `sides` that are true. Ends and crossings run straight.
+    fn joining(sides: [bool; 4]) -> Self {
+        match sides {
+            [true, true, _, _] => Dir::Hor,
+            [_, _, true, true] => Dir::Ver,
+            [false, true, false, true] => Dir::UL,
+            [true, false, false, true] => Dir::UR,
+            [false, true, true, false] => Dir::DL,
+            [true, false, true, false] => Dir::DR,
+            [_, _, false, false] => Dir::Hor,
+            _ => Dir::Ver,
+        }
+    }
 }
 
-#[derive(Default)]
+#[derive(Default, Serialize, Deserialize)]
 enum MapElem {
     #[default]
     Void,
-    Room(Space),
-    Hall(Straight),
-    Door(Position),
+    Room(Shape),
+    Hall(Path),
+    Door(Position, Door),
+}
+impl MapElem {
+    /// The kind of tile the area is made of. Which way each tile of a hall
+    /// runs is up to `Map::area_tile`.
+    fn tile(&self) -> Option<TileKind> { use TileKind::*;
+        match self {
+            MapElem::Room(_) => Some(Room),
+            MapElem::Hall(_) => Some(Hall(Dir::Hor)),
+            MapElem::Door(_, crate::Door::Secret) => None,
+            MapElem::Door(_, door) => Some(Door(*door)),
+            MapElem::Void => None,
+        }
+    }
+
+    fn points(&self) -> Vec<Position> {
+        match self {
+            MapElem::Room(room) => room.into_iter().collect(),
+            MapElem::Hall(hall) => hall.into_iter().collect(),
+            MapElem::Door(door, _) => vec![*door],
+            MapElem::Void => Vec::new(),
+        }
+    }
+
+    /// Whether actors can step into the area.
+    fn is_passable(&self) -> bool {
+        !matches!(self, MapElem::Door(_, door) if *door != Door::Open)
+    }
 }
 impl Area for MapElem {
     fn contains(&self, p: Position) -> bool {
         match self {
             MapElem::Room(room) => room.contains(p),
             MapElem::Hall(hall) => hall.contains(p),
-            MapElem::Door(door) => door.contains(p),
+            MapElem::Door(door, _) => door.contains(p),
             _ => false,
         }
     }
@@ -217,7 +587,7 @@ impl Area for MapElem {
         match self {
             MapElem::Room(room) => room.middle(),
             MapElem::Hall(hall) => hall.middle(),
-            MapElem::Door(door) => door.middle(),
+            MapElem::Door(door, _) => door.middle(),
             _ => panic!("out of bounds"),
         }
     }
@@ -225,48 +595,48 @@ impl Area for MapElem {
 
 //Mapgeneration
 
-///Generates a premade gameworld. Will implement random level generation later.
+///Generates a premade gameworld. See `generate_world` for random levels.
 pub fn custom_world() -> GameWorld {
-    let mut map = Map(Graph::new_undirected());
+    let mut map = Map::new();
 
     let a = map.add_node(room(10, 4, 5, 3));
     let b = map.add_node(room(5, 10, 11, 4));
     let c = map.add_node(room(24, 12, 8, 2));
     let d = map.add_node(room(25, 3, 2, 5));
     let e = map.add_node(room(28, 3, 4, 3));
-    let a1 = map.add_node(door(15, 5));
-    let b1 = map.add_node(door(16, 11));
-    let c1 = map.add_node(door(23, 12));
-    let c2 = map.add_node(door(26, 11));
-    let d1 = map.add_node(door(26, 8));
-    let de = map.add_node(door(27, 4));
-    let p = map.add_node(hall(18, 5, Y(8)));
-    let a1p = map.add_node(hall(16, 5, X(2)));
-    let b1p = map.add_node(hall(17, 11, X(1)));
-    let pc1 = map.add_node(hall(19, 12, X(4)));
-    let d1c2 = map.add_node(hall(26, 9, Y(2)));
-
-    map.extend_with_edges(&[
+    let a1 = map.add_node(door(15, 5, Door::Open));
+    let b1 = map.add_node(door(16, 11, Door::Open));
+    let c1 = map.add_node(door(23, 12, Door::Open));
+    let c2 = map.add_node(door(26, 11, Door::Open));
+    let d1 = map.add_node(door(26, 8, Door::Open));
+    let de = map.add_node(door(27, 4, Door::Closed));
+    let a1c1 = map.add_node(hall(&[(16, 5, X(2)), (18, 5, Y(8)), (19, 12, X(4))]));
+    let b1p = map.add_node(hall(&[(17, 11, X(1))]));
+    let d1c2 = map.add_node(hall(&[(26, 9, Y(2))]));
+
+    map.extend_with_edges([
         (a, a1), (b, b1), (c, c1), (c, c2), (d, d1), (d, de), (de, e),
-        (a1, a1p), (a1p, p),
-        (b1, b1p), (b1p, p),
-        (p, pc1), (pc1, c1),
+        (a1, a1c1), (a1c1, c1),
+        (b1, b1p), (b1p, a1c1),
         (d1, d1c2), (d1c2, c2),
     ]);
 
-    let player = Player { pos: map[a].middle(), area: a };
+    let player = Player::new(map[a].middle(), a);
+
+    map.stairs.insert(map[e].middle(), Stairs::Down);
 
-    GameWorld { player, map }
+    let dungeon = Dungeon { seed: 0, width: 40, height: 20, max_partition: 4 };
+    GameWorld::new(Level::new(map, Vec::new(), Vec::new()), player, dungeon, 0)
 }
 
-fn room(x: u16, y: u16, w: u16, h: u16) -> MapElem {
-    MapElem::Room(Rect::new(x, y, w, h))
+fn room(x: i32, y: i32, w: i32, h: i32) -> MapElem {
+    MapElem::Room(Shape::Rect(Rect::new(x, y, w, h)))
 }
-fn hall(x: u16, y: u16, l: Coordinate) -> MapElem {
-    MapElem::Hall(Line::new(x, y, l))
+fn hall(lines: &[(i32, i32, Coordinate)]) -> MapElem {
+    MapElem::Hall(Path(lines.iter().map(|&(x, y, l)| Line::new(x, y, l)).collect()))
 }
-fn door(x: u16, y: u16) -> MapElem {
-    MapElem::Door(Point { x, y })
+fn door(x: i32, y: i32, state: Door) -> MapElem {
+    MapElem::Door(Point { x, y }, state)
 }
 
 // #Diverse implementeringer
@@ -294,11 +664,7 @@ impl Deref for Map {
     type Target = Graph<MapElem, (), Undirected>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
-    }
-} impl DerefMut for Map {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &self.graph
     }
 }
 
@@ -0,0 +1,38 @@
+use super::{GameWorld, MonsterKind};
+
+/// How many messages are kept for the scrollback.
+const HISTORY: usize = 200;
+
+impl GameWorld {
+    /// What has happened in the game, oldest first.
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+
+    /// Tells the player something happened.
+    pub(super) fn log(&mut self, message: String) {
+        if self.messages.len() >= HISTORY {
+            self.messages.remove(0);
+        }
+        self.messages.push(message);
+    }
+}
+
+impl MonsterKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            MonsterKind::Rat => "rat",
+            MonsterKind::Goblin => "goblin",
+            MonsterKind::Zombie => "zombie",
+        }
+    }
+
+    /// How the monster attacks, as in "The rat bites you."
+    pub fn verb(self) -> &'static str {
+        match self {
+            MonsterKind::Rat => "bites",
+            MonsterKind::Goblin => "hits",
+            MonsterKind::Zombie => "claws",
+        }
+    }
+}
@@ -1,40 +1,300 @@
-use std::io::{self, Result, Write};
+use std::{env, fs, path::Path, io::{self, Result, Error, ErrorKind}};
 use crossterm::{execute, terminal, cursor, event::KeyCode};
+use rogue::{GameWorld, Actor, write, menu::Menu, util::Input, render::{Renderer, Terminal}};
 
 const CLEAR_ALL: terminal::Clear = terminal::Clear(terminal::ClearType::All);
+/// Where the game is saved on quit, and continued from.
+const SAVE_FILE: &str = "rogue.save";
+/// The sizes a new world can be generated in.
+const SIZES: [(&str, i32, i32); 3] = [("small", 80, 30), ("medium", 120, 40), ("large", 160, 60)];
 
 fn main() -> Result<()> {
     //Setup
     let mut screen = io::stdout();
+    let seed = match env::args().nth(1) {
+        Some(arg) => Some(arg.parse().map_err(|_|
+            Error::new(ErrorKind::InvalidInput, "seed must be a number"))?),
+        None => None,
+    };
 
     terminal::enable_raw_mode()?;
     execute!(screen, cursor::Hide, CLEAR_ALL)?;
     
     //Run game
-    run(&mut screen)?;
+    let last_seed = main_menu(&mut Terminal(&mut screen), seed)?;
 
     //Cleanup
     terminal::disable_raw_mode()?;
     execute!(screen, CLEAR_ALL, cursor::MoveTo(0, 0), cursor::Show)?;
+    if let Some(seed) = last_seed {
+        println!("Seed: {seed}");
+    }
     
     Ok(())
 }
 
+struct Options {
+    /// Index into `SIZES`.
+    size: usize,
+    show_seed: bool,
+}
+
+/// How the player left a game.
+enum Exit {
+    /// To the main menu, so the game can be continued.
+    Menu,
+    Died,
+    Quit,
+}
+
+/// Shows the main menu until the player quits. The first new game uses
+/// `seed` if there is one. The game is saved on quit, and continuing
+/// without a game in progress loads it. Dying in a loaded game deletes the
+/// save, but dying in a new one leaves it alone. Returns the seed of the
+/// last game played.
+fn main_menu(out: &mut impl Renderer, mut seed: Option<u64>) -> Result<Option<u64>> {
+    let mut menu = Menu::new("Rogue", &["New game", "Continue", "Options", "Quit"]);
+    let mut options = Options { size: 1, show_seed: true };
+    let mut game: Option<(GameWorld, u64)> = None;
+    // Whether `game` was loaded from the save file.
+    let mut from_save = false;
+    let mut last_seed = None;
+
+    loop {
+        match choose(out, &mut menu)? {
+            Some(0) => {
+                let seed = seed.take().unwrap_or_else(rand::random);
+                let (_, width, height) = SIZES[options.size];
+                game = Some((rogue::generate_world(width, height, 6, seed), seed));
+                from_save = false;
+            }
+            Some(1) if game.is_none() => match GameWorld::load(Path::new(SAVE_FILE)) {
+                Ok(loaded) => {
+                    game = Some(loaded);
+                    from_save = true;
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    tell(out, "There is no saved game.")?;
+                    continue;
+                }
+                Err(e) => {
+                    tell(out, &format!("Could not load the game: {e}"))?;
+                    continue;
+                }
+            },
+            Some(1) => {}
+            Some(2) => {
+                options_menu(out, &mut options)?;
+                continue;
+            }
+            Some(3) => break,
+            _ => continue,
+        }
+        let Some((world, seed)) = &mut game else { continue };
+        last_seed = Some(*seed);
+
+        match run(out, world, *seed, &options)? {
+            Exit::Menu => {}
+            Exit::Died => {
+                game = None;
+                if from_save {
+                    match fs::remove_file(SAVE_FILE) {
+                        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+                        _ => {}
+                    }
+                }
+            }
+            Exit::Quit => break,
+        }
+    }
+
+    if let Some((world, seed)) = &game {
+        world.save(*seed, Path::new(SAVE_FILE))?;
+    }
+    Ok(last_seed)
+}
+
+/// Shows a message until the player presses a key.
+fn tell(out: &mut impl Renderer, message: &str) -> Result<()> {
+    choose(out, &mut Menu::new(message, &["OK"]))?;
+    Ok(())
+}
+
+fn options_menu(out: &mut impl Renderer, options: &mut Options) -> Result<()> {
+    let mut menu = Menu::new("Options", &["", "", "Back"]);
+    loop {
+        menu.set_item(0, format!("Map size:  {}", SIZES[options.size].0));
+        menu.set_item(1, format!("Show seed: {}", if options.show_seed { "on" } else { "off" }));
+
+        match choose(out, &mut menu)? {
+            Some(0) => options.size = (options.size + 1) % SIZES.len(),
+            Some(1) => options.show_seed = !options.show_seed,
+            _ => return Ok(()),
+        }
+    }
+}
+
+/// Asks what to do with the paused game. Quitting has to be confirmed.
+fn pause_menu(out: &mut impl Renderer) -> Result<Option<Exit>> {
+    let mut menu = Menu::new("Paused", &["Resume", "Main menu", "Quit"]);
+    loop {
+        match choose(out, &mut menu)? {
+            Some(1) => return Ok(Some(Exit::Menu)),
+            Some(2) => {
+                let mut confirm = Menu::new("Are you sure?", &["No", "Yes"]);
+                if choose(out, &mut confirm)? == Some(1) {
+                    return Ok(Some(Exit::Quit));
+                }
+            }
+            _ => return Ok(None),
+        }
+    }
+}
+
+/// Shows `menu` until the player picks an item, or `None` on escape.
+fn choose(out: &mut impl Renderer, menu: &mut Menu) -> Result<Option<usize>> {
+    loop {
+        let (width, height) = terminal::size()?;
+        menu.print(out, width, height)?;
+
+        match rogue::util::input_event()? {
+            Input::Resize(..) => {}
+            Input::Key(KeyCode::Esc) => return Ok(None),
+            Input::Key(key) => {
+                if let Some(i) = menu.select(key) {
+                    return Ok(Some(i));
+                }
+            }
+        }
+    }
+}
+
 /// Repeatedly takes input from the player and writes to the screen.
-/// Function exits when player presses escape or error occurs.
-fn run(out: &mut impl Write) -> Result<()> {
-    let mut world = rogue::custom_world();
+/// Function exits when the player leaves through the pause menu, dies,
+/// or an error occurs.
+fn run(
+    out: &mut impl Renderer, world: &mut GameWorld, seed: u64, options: &Options,
+) -> Result<Exit> {
+    let seed = options.show_seed.then_some(seed);
+    let (mut width, mut height) = terminal::size()?;
 
-    world.print(out, Default::default())?;
+    redraw(out, world, seed, width, height)?;
 
     loop {
-        let key = rogue::util::input()?;
-        if let KeyCode::Esc = key {
-            break Ok(());
+        match rogue::util::input_event()? {
+            Input::Resize(w, h) => {
+                (width, height) = (w, h);
+                redraw(out, world, seed, width, height)?;
+            }
+            Input::Key(KeyCode::Esc) => {
+                if let Some(exit) = pause_menu(out)? {
+                    break Ok(exit);
+                }
+                (width, height) = terminal::size()?;
+                redraw(out, world, seed, width, height)?;
+            }
+            Input::Key(key) => {
+                if !write::fits(width, height) {
+                    continue;
+                }
+                match key {
+                    KeyCode::Char('m') => {
+                        show_messages(out, world, width, height)?;
+                        (width, height) = terminal::size()?;
+                        redraw(out, world, seed, width, height)?;
+                        continue;
+                    }
+                    KeyCode::Char('i') => {
+                        world.print_inventory(out, "Inventory")?;
+                        rogue::util::input()?;
+                        (width, height) = terminal::size()?;
+                        redraw(out, world, seed, width, height)?;
+                        continue;
+                    }
+                    // The inventory covers the map, so it is drawn again after the change,
+                    // at the size the terminal is now.
+                    KeyCode::Char(c @ ('d' | 'u')) => {
+                        let title = if c == 'd' { "Drop which item?" } else { "Use which item?" };
+                        world.print_inventory(out, title)?;
+                        let key = rogue::util::input()?;
+                        let next = if c == 'd' { world.drop_item(key) } else { world.use_item(key) };
+                        world.update(next);
+                        (width, height) = terminal::size()?;
+                        redraw(out, world, seed, width, height)?;
+                    }
+                    // The whole screen shows a different level after the stairs,
+                    // and a door changes what the player can see.
+                    KeyCode::Char('<' | '>' | 'o' | 'c' | 's') => {
+                        let next = world.next(key);
+                        world.update(next);
+                        redraw(out, world, seed, width, height)?;
+                    }
+                    _ => {
+                        let next = world.next(key);
+                        world.print(out, next)?;
+                        world.update(next);
+                    }
+                }
+
+                while let Actor::Monster(i) = world.next_actor() {
+                    let next = world.next_monster(i);
+                    world.print(out, next)?;
+                    world.update(next);
+                    if world.is_over() {
+                        break;
+                    }
+                }
+                world.print_hud(out)?;
+                if world.is_over() {
+                    world.print_results(out)?;
+                    rogue::util::input()?;
+                    break Ok(Exit::Died);
+                }
+            }
+        }
+    }
+}
+
+/// Shows the message history until the player presses a key other than
+/// up or down, which scroll it. The history fills the screen again when
+/// the terminal is resized.
+fn show_messages(
+    out: &mut impl Renderer, world: &GameWorld, mut width: u16, mut height: u16,
+) -> Result<()> {
+    let mut scroll = 0;
+    loop {
+        if write::fits(width, height) {
+            world.print_messages(out, width, height, scroll)?;
+        } else {
+            write::print_too_small(out, width, height)?;
         }
+        match rogue::util::input_event()? {
+            Input::Resize(w, h) => (width, height) = (w, h),
+            Input::Key(KeyCode::Up) => {
+                scroll = (scroll + 1).min(world.messages().len().saturating_sub(1));
+            }
+            Input::Key(KeyCode::Down) => scroll = scroll.saturating_sub(1),
+            Input::Key(_) => return Ok(()),
+        }
+    }
+}
 
-        let next = world.next(key);
-        world.print(out, next)?;
-        world.update(next);
+/// Lays the screen out again and draws all of it.
+fn redraw(
+    out: &mut impl Renderer, world: &mut GameWorld,
+    seed: Option<u64>, width: u16, height: u16,
+) -> Result<()> {
+    if !write::fits(width, height) {
+        return write::print_too_small(out, width, height);
+    }
+    world.resize(width, height);
+
+    out.clear()?;
+    world.print(out, Default::default())?;
+    world.print_hud(out)?;
+    if let Some(seed) = seed {
+        out.colour(None)?;
+        out.print(0, height - 1, &format!("Seed: {seed}"))?;
     }
+    out.flush()
 }
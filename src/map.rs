@@ -1,40 +1,290 @@
-use crate::{points::{Space, Point, Rect}, Map};
+use crate::{
+    points::{Space, Point, Rect, Line, Straight, Path, Position, Coord::{X, Y}, Area,
+        shape::{Shape, Circle, Cross, Union, Mask}},
+    Map, MapElem, GameWorld, Player, Monster, MonsterKind, ItemKind, Stairs, Door,
+    item::Item, dungeon::{Dungeon, Level},
+};
+use strum::IntoEnumIterator;
 use num_rational::Ratio;
+use petgraph::graph::NodeIndex;
 use rand::prelude::*;
+use rand_chacha::ChaCha12Rng;
+
+/// Generates a random gameworld by partitioning a `width` x `height` space
+/// at most `max_partition` times, placing a room in every partition and
+/// connecting the rooms of sibling partitions with halls. Deeper levels are
+/// generated the same way when the player takes the stairs down.
+/// The same `seed` always generates the same world.
+pub fn generate_world(
+    width: i32, height: i32, max_partition: u8, seed: u64,
+) -> GameWorld {
+    generate_layout(width, height, max_partition, seed).world
+}
+
+/// A generated gameworld together with the stages it was generated from.
+pub struct Layout {
+    pub partitions: Vec<Space>,
+    pub rooms: Vec<Shape>,
+    pub halls: Vec<Straight>,
+    pub doors: Vec<Position>,
+    pub world: GameWorld,
+}
+
+/// Same as `generate_world`, but keeps the stages of the generation.
+pub fn generate_layout(
+    width: i32, height: i32, max_partition: u8, seed: u64,
+) -> Layout {
+    assert!(width >= MIN_SIZE && height >= MIN_SIZE, "world too small");
+    let dungeon = Dungeon { seed, width, height, max_partition };
+    let mut rng = RngWrapper::new(dungeon.level_seed(1));
+
+    let areas = rng.rand_spaces(space_size(width, height), max_partition);
+    let (level, leaves, start) = rng.rand_level(&areas, 1);
+    let player = Player::new(start, level.map.areas[&start]);
+
+    let mut halls = Vec::new();
+    let mut doors = Vec::new();
+    for area in level.map.node_weights() {
+        match area {
+            MapElem::Hall(hall) => halls.extend(&hall.0),
+            MapElem::Door(door, _) => doors.push(*door),
+            _ => {}
+        }
+    }
+
+    Layout {
+        partitions: areas.leaf_iter().copied().collect(),
+        rooms: leaves.into_iter().map(|l| l.room).collect(),
+        halls, doors,
+        world: GameWorld::new(level, player, dungeon, rng.gen()),
+    }
+}
 
-/*
-pub fn generate_world(width: u16, height: u16, max_partition: u8) -> GameWorld {
-    todo!()
-} */
+/// Generates the level at `depth` of `dungeon`, with the up stairs where
+/// the player starts.
+pub(super) fn generate_level(dungeon: &Dungeon, depth: u32) -> Level {
+    let mut rng = RngWrapper::new(dungeon.level_seed(depth));
+    let space = space_size(dungeon.width, dungeon.height);
+    let areas = rng.rand_spaces(space, dungeon.max_partition);
+    rng.rand_level(&areas, depth).0
+}
 
-fn space_size(width: u16, height: u16) -> Space {
-    Rect::new(0, 0, width, height)
+/// The space starts at (1, 1), leaving the outermost row and column free.
+fn space_size(width: i32, height: i32) -> Space {
+    Rect::new(1, 1, width, height)
 }
 
-const MIN_SIZE: u16 = 4;
-const MIN_ROOM_SIZE: u16 = 2;
-const MIN_ROOM_AREA: u16 = 8;
-const VARIANCE: Ratio<u16> = Ratio::new_raw(1, 2);
+/// Smallest width and height of a partition. Leaves room for a room of at
+/// least 3 x 3 tiles, a wall on each side and a lane for halls.
+pub const MIN_SIZE: i32 = 6;
+const MIN_ROOM_SIZE: i32 = 2;
+const MIN_ROOM_AREA: i32 = 8;
+const VARIANCE: Ratio<i32> = Ratio::new_raw(1, 2);
+/// The chance of a room other than the starting room getting a monster.
+const MONSTER_CHANCE: (u32, u32) = (1, 2);
+/// The chance of a room getting an item.
+const ITEM_CHANCE: (u32, u32) = (1, 3);
+/// The chance of a room being a circle, cross, union of rectangles or
+/// some other shape rather than a rectangle.
+const IRREGULAR_ROOM_CHANCE: (u32, u32) = (1, 2);
+/// The chance of a door being generated open rather than closed.
+const OPEN_DOOR_CHANCE: (u32, u32) = (1, 2);
+/// The chance of a door looking like a wall until it is searched for.
+const SECRET_DOOR_CHANCE: (u32, u32) = (1, 12);
+/// The chance of a level having a locked door, and a key for it.
+const LOCKED_DOOR_CHANCE: (u32, u32) = (1, 2);
+
+/// A room placed in a leaf of the partition tree.
+struct Leaf {
+    space: Space,
+    room: Shape,
+    index: NodeIndex,
+}
 
 impl RngWrapper {
-    fn new() -> Self { Self(thread_rng()) }
+    fn new(seed: u64) -> Self { Self(ChaCha12Rng::seed_from_u64(seed)) }
+
+    /// Fills the partitions with a level. The player starts in the middle of
+    /// a random room, on the up stairs below the first level. The down stairs
+    /// go in another room if there is one, and monsters in the rooms the
+    /// player doesn't start in. Returns where the player starts.
+    fn rand_level(&mut self, areas: &Areas, depth: u32) -> (Level, Vec<Leaf>, Position) {
+        let (mut map, leaves) = self.rand_map(areas);
+
+        let start = leaves.choose(&mut **self).expect("at least one room");
+        let others: Vec<_> = leaves.iter().filter(|l| l.index != start.index).collect();
+        let down = loop {
+            let leaf = others.choose(&mut **self).copied().unwrap_or(start);
+            let pos = self.rand_point(&leaf.room);
+            if pos != start.room.middle() {
+                break pos;
+            }
+        };
+        map.stairs.insert(down, Stairs::Down);
+        if depth > 1 {
+            map.stairs.insert(start.room.middle(), Stairs::Up);
+        }
+
+        let mut monsters = Vec::new();
+        for leaf in &others {
+            if self.gen_ratio(MONSTER_CHANCE.0, MONSTER_CHANCE.1) {
+                monsters.push(self.rand_monster(leaf));
+            }
+        }
 
-    fn rand_map(&mut self, areas: Areas) -> Map {
-        if areas.is_leaf() {
-            let room = self.rand_room(areas.val);
+        let mut items = Vec::new();
+        for leaf in &leaves {
+            if self.gen_ratio(ITEM_CHANCE.0, ITEM_CHANCE.1) {
+                items.push(self.rand_item(leaf));
+            }
+        }
+
+        let doors: Vec<_> = map.node_indices()
+            .filter(|index| matches!(map[*index], MapElem::Door(..)))
+            .collect();
+        for &index in &doors {
+            if self.gen_ratio(SECRET_DOOR_CHANCE.0, SECRET_DOOR_CHANCE.1) {
+                map.set_door(index, Door::Secret);
+            } else if !self.gen_ratio(OPEN_DOOR_CHANCE.0, OPEN_DOOR_CHANCE.1) {
+                map.set_door(index, Door::Closed);
+            }
+        }
+        if self.gen_ratio(LOCKED_DOOR_CHANCE.0, LOCKED_DOOR_CHANCE.1) {
+            items.extend(self.rand_lock(&mut map, &doors, &leaves, start.index));
         }
-        todo!()
+
+        let start = start.room.middle();
+        (Level::new(map, monsters, items), leaves, start)
     }
 
-    // generating rooms
-    fn rand_rooms(&mut self, areas: Areas) -> Vec<Space> {
-        let mut rooms = Vec::new();
-        for space in areas.leaf_iter() {
-            rooms.push(self.rand_room(*space))
+    // generating the map
+    fn rand_map(&mut self, areas: &Areas) -> (Map, Vec<Leaf>) {
+        let mut map = Map::new();
+        let leaves = self.rand_subtree(areas, &mut map);
+        (map, leaves)
+    }
+
+    /// Adds the rooms of a subtree to the map and connects its two halves.
+    fn rand_subtree(&mut self, node: &Areas, map: &mut Map) -> Vec<Leaf> {
+        let (Some(l), Some(r)) = (&node.left, &node.right) else {
+            let bounds = self.rand_room(room_space(node.val));
+            let room = self.rand_shape(bounds);
+            let index = map.add_node(MapElem::Room(room.clone()));
+            return vec![Leaf { space: node.val, room, index }];
+        };
+        let mut left = self.rand_subtree(l, map);
+        let right = self.rand_subtree(r, map);
+
+        if l.end.x < node.end.x {
+            self.connect_x(l.end.x, &left, &right, map);
+        } else {
+            self.connect_y(l.end.y, &left, &right, map);
         }
-        rooms
+        left.extend(right);
+        left
+    }
+
+    /// Connects a room left of the split at `x` with a room right of it.
+    /// The hall runs down the lane just left of the split.
+    fn connect_x(&mut self, x: i32, left: &[Leaf], right: &[Leaf], map: &mut Map) {
+        let a = self.rand_leaf(left, |l| l.space.end.x == x);
+        let b = self.rand_leaf(right, |l| l.space.pos.x == x);
+        let (ra, rb) = (a.room.bounds(), b.room.bounds());
+        let ya = self.rand_side(&a.room, |p| p.x == ra.end.x - 1).y;
+        let yb = self.rand_side(&b.room, |p| p.x == rb.pos.x).y;
+        let lane = x - 1;
+
+        let halls = [
+            Line::new_exact(ra.end.x + 1, ya, X(lane)),
+            Line::new_exact(lane, ya.min(yb), Y(ya.max(yb) + 1)),
+            Line::new_exact(x, yb, X(rb.pos.x - 1)),
+        ];
+        let doors = (
+            Point { x: ra.end.x, y: ya },
+            Point { x: rb.pos.x - 1, y: yb },
+        );
+        connect(map, (a.index, b.index), doors, halls);
     }
 
+    /// Connects a room above the split at `y` with a room below it.
+    /// The hall runs along the lane just above the split.
+    fn connect_y(&mut self, y: i32, top: &[Leaf], bottom: &[Leaf], map: &mut Map) {
+        let a = self.rand_leaf(top, |l| l.space.end.y == y);
+        let b = self.rand_leaf(bottom, |l| l.space.pos.y == y);
+        let (ra, rb) = (a.room.bounds(), b.room.bounds());
+        let xa = self.rand_side(&a.room, |p| p.y == ra.end.y - 1).x;
+        let xb = self.rand_side(&b.room, |p| p.y == rb.pos.y).x;
+        let lane = y - 1;
+
+        let halls = [
+            Line::new_exact(xa, ra.end.y + 1, Y(lane)),
+            Line::new_exact(xa.min(xb), lane, X(xa.max(xb) + 1)),
+            Line::new_exact(xb, y, Y(rb.pos.y - 1)),
+        ];
+        let doors = (
+            Point { x: xa, y: ra.end.y },
+            Point { x: xb, y: rb.pos.y - 1 },
+        );
+        connect(map, (a.index, b.index), doors, halls);
+    }
+
+    /// Picks a random tile of `room` on the side of its bounds a door goes
+    /// through. Every room shape reaches all four sides of its bounds.
+    fn rand_side(&mut self, room: &Shape, on_side: impl Fn(Position) -> bool) -> Position {
+        let tiles: Vec<_> = room.into_iter().filter(|p| on_side(*p)).collect();
+        *tiles.choose(&mut **self).expect("rooms reach their bounds")
+    }
+
+    /// Picks a random leaf bordering the split.
+    fn rand_leaf<'a>(
+        &mut self, leaves: &'a [Leaf], borders: impl Fn(&Leaf) -> bool,
+    ) -> &'a Leaf {
+        let candidates: Vec<_> = leaves.iter().filter(|l| borders(l)).collect();
+        candidates.choose(&mut **self).expect("a leaf borders every split")
+    }
+
+    /// Locks one of the `doors`, and gives back a key lying in a room that
+    /// can be reached from the `start` room without passing the lock or a
+    /// secret door.
+    fn rand_lock(
+        &mut self, map: &mut Map, doors: &[NodeIndex], leaves: &[Leaf], start: NodeIndex,
+    ) -> Option<Item> {
+        let candidates: Vec<_> = doors.iter()
+            .filter(|index| !matches!(map[**index], MapElem::Door(_, Door::Secret)))
+            .collect();
+        let &&door = candidates.choose(&mut **self)?;
+        map.set_door(door, Door::Locked);
+
+        let reachable = map.reachable(start, |area| {
+            !matches!(area, MapElem::Door(_, Door::Locked | Door::Secret))
+        });
+        let rooms: Vec<_> = leaves.iter().filter(|l| reachable.contains(&l.index)).collect();
+        let room = &rooms.choose(&mut **self).expect("the start room is reachable").room;
+        Some(Item { kind: ItemKind::Key, pos: self.rand_point(room) })
+    }
+
+    // generating monsters and items
+    fn rand_monster(&mut self, leaf: &Leaf) -> Monster {
+        let pos = self.rand_point(&leaf.room);
+        let kind = MonsterKind::iter().choose(&mut **self).unwrap();
+        Monster::new(kind, pos, leaf.index)
+    }
+
+    fn rand_item(&mut self, leaf: &Leaf) -> Item {
+        let pos = self.rand_point(&leaf.room);
+        let kind = ItemKind::iter()
+            .filter(|kind| *kind != ItemKind::Key)
+            .choose(&mut **self)
+            .unwrap();
+        Item { kind, pos }
+    }
+
+    fn rand_point(&mut self, room: &Shape) -> Position {
+        let tiles: Vec<_> = room.into_iter().collect();
+        *tiles.choose(&mut **self).expect("rooms are not empty")
+    }
+
+    // generating rooms
     fn rand_room(&mut self, space: Space) -> Space {
         let Rect {
             pos: Point { x, y },
@@ -54,7 +304,68 @@ impl RngWrapper {
         }
     }
 
-    fn rand_wall(&mut self, pos: u16, end: u16) -> (u16, u16) {
+    /// Fills the `bounds` of a room with a random shape. Every shape reaches
+    /// all four sides of its bounds, so the doors can go anywhere along them.
+    /// Shapes the bounds are too small for stay rectangles.
+    fn rand_shape(&mut self, bounds: Space) -> Shape {
+        if !self.gen_ratio(IRREGULAR_ROOM_CHANCE.0, IRREGULAR_ROOM_CHANCE.1) {
+            return Shape::Rect(bounds);
+        }
+        let Rect { pos, end } = bounds;
+        let Point { x: width, y: height } = bounds.size();
+        let middle = Point { x: pos.x + (width - 1) / 2, y: pos.y + (height - 1) / 2 };
+
+        let shape = match self.gen_range(0..4) {
+            0 => Shape::Circle(Circle { centre: middle, radius: (width.min(height) - 1) / 2 }),
+            1 if width >= 3 && height >= 3 => {
+                let thick = |rng: &mut Self, size: i32| rng.gen_range(1..=(size - 1) / 2);
+                let (w, h) = (thick(self, width), thick(self, height));
+                let (x, y) = (pos.x + (width - w) / 2, pos.y + (height - h) / 2);
+                Shape::Cross(Cross {
+                    hor: Rect::new_exact(pos.x, y, end.x, y + h),
+                    ver: Rect::new_exact(x, pos.y, x + w, end.y),
+                })
+            }
+            2 => {
+                let h = self.gen_range(1..=height);
+                let w = self.gen_range(1..=width);
+                let (row_y, col_x) = (
+                    if self.gen() { pos.y } else { end.y - h },
+                    if self.gen() { pos.x } else { end.x - w },
+                );
+                Shape::Union(Union(vec![
+                    Rect::new_exact(pos.x, row_y, end.x, row_y + h),
+                    Rect::new_exact(col_x, pos.y, col_x + w, end.y),
+                ]))
+            }
+            _ => {
+                // The rows narrow away from the middle row, but never past
+                // the middle column, so the shape holds together.
+                let mut spans = vec![(pos.x, end.x); height as usize];
+                let below: Vec<_> = (middle.y + 1..end.y).collect();
+                let above: Vec<_> = (pos.y..middle.y).rev().collect();
+                for rows in [below, above] {
+                    let (mut left, mut right) = (pos.x, end.x);
+                    for y in rows {
+                        left = (left + self.gen_range(0..=1)).min(middle.x);
+                        right = (right - self.gen_range(0..=1)).max(middle.x + 1);
+                        spans[(y - pos.y) as usize] = (left, right);
+                    }
+                }
+                Shape::Mask(Mask::new(bounds, |p| {
+                    let (left, right) = spans[(p.y - pos.y) as usize];
+                    left <= p.x && p.x < right
+                }))
+            }
+        };
+
+        if (&shape).into_iter().count() < MIN_ROOM_AREA as usize {
+            return Shape::Rect(bounds);
+        }
+        shape
+    }
+
+    fn rand_wall(&mut self, pos: i32, end: i32) -> (i32, i32) {
         let a = self.gen_range(pos..=end);
         let b = self.gen_range(pos..=end);
 
@@ -76,9 +387,7 @@ impl RngWrapper {
     fn split_space(&mut self, space: Space) -> Option<(Space, Space)> {
         let Point { x: width, y: height } = space.size();
 
-        if self.gen_ratio(
-            width.into(), (width + height).into()
-        ) {
+        if self.gen_range(0..width + height) < width {
             //Split on X axis
             let x = self.split_size(width, space.pos.x)?;
             Some((space.end_x(x), space.pos_x(x)))
@@ -89,8 +398,8 @@ impl RngWrapper {
         }
     }
 
-    fn split_size(&mut self, size: u16, scale: u16) -> Option<u16> {
-        const ONE: Ratio<u16> = Ratio::new_raw(1, 1);
+    fn split_size(&mut self, size: i32, scale: i32) -> Option<i32> {
+        const ONE: Ratio<i32> = Ratio::new_raw(1, 1);
         let pad = (((ONE - VARIANCE) * size) / 2).to_integer();
         let partition = self.gen_range(pad..(size-pad));
 
@@ -100,172 +409,47 @@ impl RngWrapper {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    
-    const WIDTH: u16 = 30;
-    const HEIGHT: u16 = 30;
-    const MAX_DEPTH: u8 = 4;
-
-    #[test]
-    fn rooms_generation() {
-        let mut out = std::io::stdout();
-        let mut rng = RngWrapper::new();
-        let space = space_size(WIDTH, HEIGHT);
-        loop {
-            let areas = rng.rand_spaces(space, MAX_DEPTH);
-
-            queue!(out, CLEAR_ALL).unwrap();
-            for (i, space) in areas.leaf_iter().enumerate() {
-                let c = char::from_digit(i as u32, 32).unwrap();
-                rng.rand_room(*space).draw(&mut out, c).unwrap();
-            }
-            out.flush().unwrap();
-
-            if let KeyCode::Esc = crate::util::input().unwrap() { break; }
-        }
-    }
-
-    #[test]
-    fn room_generation() {
-        let mut out = std::io::stdout();
-        let mut rng = RngWrapper::new();
-        let space = Space::new(5, 3 , 5, 5);
-        loop {
-            let room = rng.rand_room(space);
-
-            queue!(out, CLEAR_ALL).unwrap();
-            space.draw_edge(&mut out, '+').unwrap();
-            room.draw(&mut out, '.').unwrap();
-            out.flush().unwrap();
-
-            if let KeyCode::Esc = crate::util::input().unwrap() { break; }
-        }
-    }
-
-    #[test]
-    fn tree_generation() {
-        let mut out = std::io::stdout();
-        let mut rng = RngWrapper::new();
-        let space = space_size(WIDTH, HEIGHT);
-        loop {
-            let tree = rng.rand_spaces(space, MAX_DEPTH);
-
-            queue!(out, CLEAR_ALL).unwrap();
-            for (i, space) in tree.leaf_iter().enumerate() {
-                let i = char::from_digit(i as u32, 32).unwrap();
-                space.draw_tl(&mut out, '+').unwrap();
-                space.pos.downright(&mut out, i).unwrap();
-            }
-            space.draw_br(&mut out, '+').unwrap();
-            out.flush().unwrap();
-            
-            if let KeyCode::Esc = crate::util::input().unwrap() { break; }
-        }
-    }
-
-    #[test]
-    fn random_split() {
-        let mut out = std::io::stdout();
-        let mut rng = RngWrapper::new();
-
-        let space = Space::new(0, 0 , 10, 10);
-        
-        loop {
-            queue!(out, CLEAR_ALL).unwrap();
-            if let Some((l, r)) = rng.split_space(space) {
-                l.draw_edge(&mut out, 'l').unwrap();
-                r.draw_edge(&mut out, 'r').unwrap();
-            } else {
-                print!("Not valid");
-            }
-            out.flush().unwrap();
-
-            if let KeyCode::Esc = crate::util::input().unwrap() {
-                break;
-            }
-        }
-    }
-
-    use std::io::{Write, self};
-    use crossterm::{queue,
-        terminal, cursor::{MoveTo, MoveToColumn}, style::Print, event::KeyCode};
-    const CLEAR_ALL: terminal::Clear = terminal::Clear(terminal::ClearType::All);
-
-    use crate::points::{Position, Move};
-
-    impl Position {
-        fn downright(self, out: &mut impl Write, c: char) -> io::Result<()> {
-            let p = self + Move::RD;
-            queue!(out, MoveTo(p.x, p.y), Print(c))
-        }
+/// Adds the doors between two rooms to the map, and a hall of the
+/// non-empty `halls` between the doors, bending where they meet.
+/// The doors are left open.
+fn connect(
+    map: &mut Map,
+    (a, b): (NodeIndex, NodeIndex),
+    (door_a, door_b): (Position, Position),
+    halls: [Straight; 3],
+) {
+    let mut prev = map.add_node(MapElem::Door(door_a, Door::Open));
+    map.add_edge(a, prev, ());
+
+    let path = Path(halls.into_iter().filter(|hall| hall.len() > 0).collect());
+    if !path.0.is_empty() {
+        let hall = map.add_node(MapElem::Hall(path));
+        map.add_edge(prev, hall, ());
+        prev = hall;
     }
 
-    impl Space {
-        fn draw_edge(self, out: &mut impl Write, c: char) -> io::Result<()> {
-            let Self { pos, end } = self;
-            queue!(out, MoveTo(pos.x, pos.y))?;
-            for _ in pos.x..end.x {
-                queue!(out, Print(c))?;
-            }
-            for row in pos.y..end.y {
-                queue!(out,
-                    MoveTo(pos.x, row), Print(c),
-                    MoveToColumn(end.x - 1), Print(c))?;
-            }
-            queue!(out, MoveTo(pos.x, end.y - 1))?;
-            for _ in pos.x..end.x {
-                queue!(out, Print(c))?;
-            }
-    
-            Ok(())
-        }
-
-        fn draw_tl(self, out: &mut impl Write, c: char) -> io::Result<()> {
-            let Self { pos, end } = self;
-            queue!(out, MoveTo(pos.x, pos.y))?;
-            for _ in pos.x..end.x {
-                queue!(out, Print(c))?;
-            }
-            for row in pos.y..end.y {
-                queue!(out, MoveTo(pos.x, row), Print(c))?;
-            }
-
-            Ok(())
-        }
-
-        fn draw_br(self, out: &mut impl Write, c: char) -> io::Result<()> {
-            let Self { pos, end } = self;
-            for row in pos.y..end.y {
-                queue!(out, MoveTo(end.x, row), Print(c))?;
-            }
-            queue!(out, MoveTo(pos.x, end.y))?;
-            for _ in pos.x..end.x {
-                queue!(out, Print(c))?;
-            }
-
-            Ok(())
-        }
+    let door_b = map.add_node(MapElem::Door(door_b, Door::Open));
+    map.add_edge(prev, door_b, ());
+    map.add_edge(door_b, b, ());
+}
 
-        fn draw(self, out: &mut impl Write, c: char) -> io::Result<()> {
-            for row in self.pos.y..self.end.y {
-                queue!(out, MoveTo(self.pos.x, row))?;
-                for _ in self.pos.x..self.end.x {
-                    queue!(out, Print(c))?;
-                }
-            }
-            Ok(())
-        }
-    }
+/// The part of a partition rooms can be placed in. Leaves a wall on each
+/// side, and a lane for halls along the right and bottom edge.
+fn room_space(space: Space) -> Space {
+    Rect::new_exact(
+        space.pos.x + 1, space.pos.y + 1,
+        space.end.x - 2, space.end.y - 2,
+    )
 }
 
 // RNG wrapper
 use std::ops::{Deref, DerefMut};
 
-struct RngWrapper(ThreadRng);
+/// The generator behind every level. ChaCha gives the same numbers on every
+/// platform and `rand` version, so a seed always makes the same dungeon.
+struct RngWrapper(ChaCha12Rng);
 impl Deref for RngWrapper {
-    type Target = ThreadRng;
+    type Target = ChaCha12Rng;
     fn deref(&self) -> &Self::Target { &self.0 }
 } impl DerefMut for RngWrapper {
     fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
@@ -286,14 +470,10 @@ struct Node<T> {
         Self { val, left: None, right: None }
     }
 
-    fn is_leaf(&self) -> bool {
-        self.left.is_none() && self.right.is_none()
-    }
-
-    fn leaf_iter(&self) -> Leafs<T> {
+    fn leaf_iter(&self) -> Leafs<'_, T> {
         Leafs(self.leaf_iter_alt())
     }
-    fn leaf_iter_alt(&self) -> LeafsAlt<T> {
+    fn leaf_iter_alt(&self) -> LeafsAlt<'_, T> {
         LeafsAlt { stack: vec![self] }
     }
 }
@@ -338,3 +518,6 @@ struct LeafsAlt<'a, T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,284 @@
+use std::collections::HashSet;
+
+use super::*;
+use crate::{TileKind, Dir, points::Points};
+
+const WIDTH: i32 = 30;
+const HEIGHT: i32 = 30;
+const MAX_DEPTH: u8 = 4;
+const SEEDS: u64 = 500;
+
+#[test]
+fn same_seed_same_rooms() {
+    let space = space_size(WIDTH, HEIGHT);
+    let rooms = |seed| {
+        let mut rng = RngWrapper::new(seed);
+        let areas = rng.rand_spaces(space, MAX_DEPTH);
+        let (_, leaves) = rng.rand_map(&areas);
+        leaves.iter().map(|l| (l.space, l.room.clone())).collect::<Vec<_>>()
+    };
+    for seed in 0..20 {
+        assert_eq!(rooms(seed), rooms(seed));
+    }
+    assert_ne!(rooms(1), rooms(2));
+}
+
+#[test]
+fn tree_generation() {
+    let space = space_size(WIDTH, HEIGHT);
+    for seed in 0..SEEDS {
+        let tree = RngWrapper::new(seed).rand_spaces(space, MAX_DEPTH);
+        assert_eq!(space, tree.val);
+        check_children(&tree);
+    }
+
+    fn check_children(node: &Areas) {
+        let (Some(l), Some(r)) = (&node.left, &node.right) else {
+            assert!(node.left.is_none() && node.right.is_none());
+            return;
+        };
+        assert!(inside(l.val, node.val));
+        assert!(inside(r.val, node.val));
+        assert!(!overlaps(l.val, r.val));
+        check_children(l);
+        check_children(r);
+    }
+}
+
+#[test]
+fn leaves_cover_space() {
+    let space = space_size(WIDTH, HEIGHT);
+    for seed in 0..SEEDS {
+        let tree = RngWrapper::new(seed).rand_spaces(space, MAX_DEPTH);
+        let leaves: Vec<_> = tree.leaf_iter().collect();
+
+        let area: i32 = leaves.iter().map(|l| l.width() * l.height()).sum();
+        assert_eq!(WIDTH * HEIGHT, area);
+        for (i, a) in leaves.iter().enumerate() {
+            assert!(a.width() >= MIN_SIZE && a.height() >= MIN_SIZE);
+            for b in &leaves[i + 1..] {
+                assert!(!overlaps(**a, **b));
+            }
+        }
+    }
+}
+
+#[test]
+fn room_generation() {
+    let mut rng = RngWrapper::new(0);
+    for _ in 0..SEEDS {
+        let w = rng.gen_range(MIN_SIZE..20);
+        let h = rng.gen_range(MIN_SIZE..20);
+        let space = room_space(Space::new(5, 3, w, h));
+
+        let room = rng.rand_room(space);
+        let Point { x: width, y: height } = room.size();
+        assert!(inside(room, space));
+        assert!(width >= MIN_ROOM_SIZE && height >= MIN_ROOM_SIZE);
+        assert!(width * height >= MIN_ROOM_AREA);
+    }
+}
+
+#[test]
+fn room_shapes() {
+    let mut rng = RngWrapper::new(0);
+    let mut kinds = HashSet::new();
+    for _ in 0..SEEDS {
+        let w = rng.gen_range(MIN_SIZE..20);
+        let h = rng.gen_range(MIN_SIZE..20);
+        let space = rng.rand_room(room_space(Space::new(5, 3, w, h)));
+        let room = rng.rand_shape(space);
+        kinds.insert(std::mem::discriminant(&room));
+
+        let bounds = room.bounds();
+        let tiles: HashSet<_> = (&room).into_iter().collect();
+        assert!(inside(bounds, space));
+        assert!(tiles.len() >= MIN_ROOM_AREA as usize);
+        assert!(tiles.iter().all(|p| bounds.contains(*p) && room.contains(*p)));
+        assert!(tiles.contains(&room.middle()));
+        let sides = [bounds.pos.x, bounds.end.x - 1].map(|x| tiles.iter().any(|p| p.x == x))
+            .into_iter()
+            .chain([bounds.pos.y, bounds.end.y - 1].map(|y| tiles.iter().any(|p| p.y == y)));
+        assert!(sides.into_iter().all(|side| side), "{room:?} misses a side");
+        let strips: HashSet<_> = room.clone().strips().flatten().collect();
+        assert_eq!(tiles, strips);
+
+        let mut reached = HashSet::from([room.middle()]);
+        let mut queue = vec![room.middle()];
+        while let Some(p) = queue.pop() {
+            for next in [p + X(1), p - X(1), p + Y(1), p - Y(1)] {
+                if tiles.contains(&next) && reached.insert(next) {
+                    queue.push(next);
+                }
+            }
+        }
+        assert_eq!(tiles, reached, "{room:?} is in pieces");
+
+        let mut map = Map::new();
+        map.add_node(MapElem::Room(room));
+        for p in &tiles {
+            for q in Space::new(p.x - 1, p.y - 1, 3, 3) {
+                assert!(tiles.contains(&q) || map.walls.contains_key(&q), "no wall at {q:?}");
+            }
+        }
+        assert!(map.walls.keys().all(|wall| !tiles.contains(wall)));
+    }
+    assert_eq!(5, kinds.len());
+}
+
+#[test]
+fn random_split() {
+    let mut rng = RngWrapper::new(0);
+    for size in 1..100 {
+        for _ in 0..SEEDS {
+            if let Some(split) = rng.split_size(size, 10) {
+                let piece = split - 10;
+                assert!(piece >= MIN_SIZE, "size {size}, piece {piece}");
+                assert!(size - piece >= MIN_SIZE, "size {size}, piece {piece}");
+            }
+        }
+    }
+}
+
+#[test]
+fn world_generation() {
+    for seed in 0..SEEDS {
+        let Layout { partitions, rooms, world, .. } =
+            generate_layout(WIDTH, HEIGHT, MAX_DEPTH, seed);
+
+        for (space, room) in partitions.iter().zip(&rooms) {
+            assert!(inside(room.bounds(), room_space(*space)));
+        }
+        assert_eq!(1, petgraph::algo::connected_components(&*world.map));
+        assert!(world.map[world.player.area].contains(world.player.pos));
+        for monster in &world.monsters {
+            assert!(world.map[monster.area].contains(monster.pos));
+            assert!(monster.area != world.player.area);
+        }
+        for item in &world.items {
+            assert!(rooms.iter().any(|room| room.contains(item.pos)));
+        }
+        let stairs: Vec<_> = world.map.stairs.iter().collect();
+        assert!(matches!(stairs[..], [(_, Stairs::Down)]));
+        assert!(rooms.iter().any(|room| room.contains(*stairs[0].0)));
+
+        for p in Space::new(0, 0, WIDTH + 2, HEIGHT + 2) {
+            let areas = world.map.node_weights().filter(|a| a.contains(p));
+            assert!(areas.count() <= 1, "areas overlap at {p:?}");
+        }
+        for (wall, _) in world.map.walls() {
+            if let Some(tile) = world.map.contains_tile(wall) {
+                assert!(tile.is_door(), "hall through wall at {wall:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn deeper_levels() {
+    for seed in 0..50 {
+        let dungeon = Dungeon { seed, width: WIDTH, height: HEIGHT, max_partition: MAX_DEPTH };
+        let Level { map, monsters, .. } = generate_level(&dungeon, 2);
+
+        let up = map.stairs.iter().find(|(_, s)| **s == Stairs::Up).unwrap().0;
+        assert!(map.stairs.values().any(|s| *s == Stairs::Down));
+        assert!(monsters.iter().all(|m| m.area != map.areas[up]));
+        assert_ne!(dungeon.level_seed(1), dungeon.level_seed(2));
+    }
+}
+
+#[test]
+fn locked_doors_have_keys() {
+    let (mut locks, mut secrets) = (0, 0);
+    for seed in 0..SEEDS {
+        let Layout { world, .. } = generate_layout(WIDTH, HEIGHT, MAX_DEPTH, seed);
+        let map = &world.map;
+        let doors: Vec<_> = map.node_weights()
+            .filter_map(|area| match area { MapElem::Door(_, door) => Some(*door), _ => None })
+            .collect();
+        secrets += doors.iter().filter(|door| **door == Door::Secret).count();
+
+        let locked = doors.iter().filter(|door| **door == Door::Locked).count();
+        let keys: Vec<_> = world.items.iter().filter(|i| i.kind == ItemKind::Key).collect();
+        assert!(locked <= 1);
+        assert_eq!(locked, keys.len());
+        locks += locked;
+
+        let reachable = map.reachable(world.player.area, |area| {
+            !matches!(area, MapElem::Door(_, Door::Locked | Door::Secret))
+        });
+        for key in keys {
+            assert!(reachable.contains(&map.areas[&key.pos]), "key behind its lock at {:?}", key.pos);
+        }
+        assert_eq!(map.node_count(), map.reachable(world.player.area, |_| true).len());
+    }
+    assert!(locks > 0 && secrets > 0);
+}
+
+#[test]
+fn tile_lookup() {
+    for seed in 0..50 {
+        let Layout { world, .. } = generate_layout(WIDTH, HEIGHT, MAX_DEPTH, seed);
+        let map = &world.map;
+
+        for p in Space::new(0, 0, WIDTH + 2, HEIGHT + 2) {
+            let scanned = map.node_indices()
+                .find(|i| map[*i].contains(p))
+                .and_then(|i| map.area_tile(i, p));
+            assert!(scanned == map.contains_tile(p), "wrong tile at {p:?}");
+        }
+    }
+}
+
+#[test]
+fn halls_bend_between_rooms() {
+    let mut bends = 0;
+    for seed in 0..50 {
+        let Layout { world, .. } = generate_layout(WIDTH, HEIGHT, MAX_DEPTH, seed);
+        let map = &world.map;
+
+        for index in map.node_indices() {
+            let MapElem::Hall(path) = &map[index] else { continue };
+            let doors: Vec<_> = map.neighbors(index).map(|i| map[i].middle()).collect();
+            assert_eq!(2, doors.len());
+            for p in path {
+                let Some(TileKind::Hall(dir)) = map.contains_tile(p) else { panic!("no hall at {p:?}") };
+                let sides = match dir {
+                    Dir::Hor => [p - X(1), p + X(1)],
+                    Dir::Ver => [p - Y(1), p + Y(1)],
+                    Dir::UL => [p + X(1), p + Y(1)],
+                    Dir::UR => [p - X(1), p + Y(1)],
+                    Dir::DL => [p + X(1), p - Y(1)],
+                    Dir::DR => [p - X(1), p - Y(1)],
+                };
+                for side in sides {
+                    assert!(path.contains(side) || doors.contains(&side), "hall ends at {p:?}");
+                }
+                bends += usize::from(!matches!(dir, Dir::Hor | Dir::Ver));
+            }
+        }
+    }
+    assert!(bends > 0);
+}
+
+fn inside(inner: Space, outer: Space) -> bool {
+    outer.pos.x <= inner.pos.x && inner.end.x <= outer.end.x &&
+    outer.pos.y <= inner.pos.y && inner.end.y <= outer.end.y
+}
+
+fn overlaps(a: Space, b: Space) -> bool {
+    a.pos.x < b.end.x && b.pos.x < a.end.x &&
+    a.pos.y < b.end.y && b.pos.y < a.end.y
+}
+
+/// Shared seeds have to keep making the same levels, whatever `rand` does.
+#[test]
+fn seeds_are_stable() {
+    let Layout { rooms, .. } = generate_layout(WIDTH, HEIGHT, MAX_DEPTH, 0);
+    let bounds: Vec<_> = rooms.iter().map(Shape::bounds).collect();
+    assert_eq!(vec![
+        Rect::new_exact(8, 12, 24, 15),
+        Rect::new_exact(8, 25, 11, 29),
+        Rect::new_exact(20, 25, 23, 28),
+    ], bounds);
+}
@@ -0,0 +1,62 @@
+use std::io::Result;
+
+use crossterm::event::KeyCode;
+
+use super::{render::Renderer, write::draw_frame};
+
+/// A list of choices the player moves between with up and down,
+/// and picks with enter.
+pub struct Menu {
+    title: String,
+    items: Vec<String>,
+    selected: usize,
+} impl Menu {
+    pub fn new(title: &str, items: &[&str]) -> Self {
+        Self {
+            title: title.to_string(),
+            items: items.iter().map(|item| item.to_string()).collect(),
+            selected: 0,
+        }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Changes the text of item `i`, such as the value of an option.
+    pub fn set_item(&mut self, i: usize, item: String) {
+        self.items[i] = item;
+    }
+
+    /// Moves the selection, and returns the selected item on enter.
+    pub fn select(&mut self, key: KeyCode) -> Option<usize> {
+        let len = self.items.len();
+        match key {
+            KeyCode::Up => self.selected = (self.selected + len - 1) % len,
+            KeyCode::Down => self.selected = (self.selected + 1) % len,
+            KeyCode::Enter => return Some(self.selected),
+            _ => {}
+        }
+        None
+    }
+
+    /// Draws the menu in a frame in the middle of a `width` x `height` screen.
+    pub fn print(&self, out: &mut impl Renderer, width: u16, height: u16) -> Result<()> {
+        let longest = self.items.iter().chain([&self.title])
+            .map(|item| item.chars().count())
+            .max().unwrap_or(0) as u16;
+        let (inner_width, inner_height) = (longest + 6, self.items.len() as u16 + 2);
+        let left = width.saturating_sub(inner_width + 2) / 2;
+        let top = height.saturating_sub(inner_height + 2) / 2;
+
+        out.clear()?;
+        draw_frame(left, left + inner_width + 1, top, top + inner_height + 1, out)?;
+        out.colour(None)?;
+        out.print(left + 2, top, &format!(" {} ", self.title))?;
+        for (i, (item, y)) in self.items.iter().zip(top + 2..).enumerate() {
+            let marker = if i == self.selected { '>' } else { ' ' };
+            out.print(left + 2, y, &format!("{marker} {item}"))?;
+        }
+        out.flush()
+    }
+}
@@ -1,26 +1,34 @@
 pub mod arithmatic;
+pub mod shape;
 
-use std::{ops::{Add, Sub}, iter};
+use std::{ops::{Add, Sub}, iter, slice};
+
+use serde::{Serialize, Deserialize};
 
 use crate::util::average;
 
-pub type Space = Rect<u16>;
-pub type Straight = Line<u16>;
-pub type Position = Point<u16>;
-pub type Coordinate = Coord<u16>;
+pub type Space = Rect<i32>;
+pub type Straight = Line<i32>;
+pub type Position = Point<i32>;
+pub type Coordinate = Coord<i32>;
 
-#[derive(Clone, Copy, PartialEq, Eq, Default, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Default, Hash, Debug, Serialize, Deserialize)]
 pub struct Point<N> { pub x: N, pub y: N }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Coord<N> { X(N), Y(N) }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Rect<N>{ pub pos: Point<N>, pub end: Point<N> }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Line<N>{ pub pos: Point<N>, pub end: Coord<N> }
 
+/// Lines chained end to end, in order. Consecutive lines meet at a bend.
+#[derive(Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+#[serde(try_from = "Vec<Straight>")]
+pub struct Path(pub Vec<Straight>);
+
 //Convenience functions
 
 // #Properties
@@ -171,6 +179,31 @@ impl Area for Straight {
     }
 }
 
+impl Area for Path {
+    fn contains(&self, p: Position) -> bool {
+        self.0.iter().any(|line| line.contains(p))
+    }
+
+    /// The tile halfway along the path.
+    fn middle(&self) -> Position {
+        let len: i32 = self.0.iter().map(Line::len).sum();
+        self.into_iter().nth(len as usize / 2).expect("path is not empty")
+    }
+}
+
+/// A saved path needs at least one line, and no line running backwards or
+/// standing still, so it has a middle.
+impl TryFrom<Vec<Straight>> for Path {
+    type Error = String;
+
+    fn try_from(lines: Vec<Straight>) -> Result<Self, String> {
+        if lines.is_empty() || lines.iter().any(|line| line.len() <= 0) {
+            return Err("path has an empty line or none at all".to_string());
+        }
+        Ok(Path(lines))
+    }
+}
+
 // # Point Iterators
 pub trait Points: Area + IntoIterator<Item = Position> {
     type StripsIter: Iterator<Item = Strip>;
@@ -180,7 +213,7 @@ pub trait Points: Area + IntoIterator<Item = Position> {
 
 pub struct Strip {
     pub next: Position,
-    end: u16,
+    end: i32,
 } impl Iterator for Strip {
     type Item = Position;
 
@@ -237,7 +270,7 @@ impl IntoIterator for Space {
 }
 pub struct RectPoints {
     next: Position,
-    left: u16,
+    left: i32,
     end: Position,
 } impl Iterator for RectPoints {
     type Item = Position;
@@ -349,3 +382,13 @@ pub struct LongStrips {
         }
     }
 }
+
+//Path
+impl<'a> IntoIterator for &'a Path {
+    type Item = Position;
+    type IntoIter = iter::Flatten<slice::Iter<'a, Straight>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter().flatten()
+    }
+}
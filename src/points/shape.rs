@@ -0,0 +1,233 @@
+use std::vec;
+
+use serde::{Serialize, Deserialize};
+
+use super::{Point, Position, Rect, Space, Area, Points, Strip};
+
+/// The tiles within `radius` of `centre`, rounded so the edges aren't pointy.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Circle { pub centre: Position, pub radius: i32 }
+
+/// A horizontal and a vertical bar crossing each other.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Cross { pub hor: Space, pub ver: Space }
+
+/// The tiles of any of the rectangles.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "Vec<Space>")]
+pub struct Union(pub Vec<Space>);
+
+/// Any tiles within `bounds`, row by row from the top left corner.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "SavedMask")]
+pub struct Mask { pub bounds: Space, pub tiles: Vec<bool> }
+
+/// A mask as it is saved, before its tiles are checked against its bounds.
+#[derive(Deserialize)]
+struct SavedMask { bounds: Space, tiles: Vec<bool> }
+
+/// The shape of a room.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Shape {
+    Rect(Space),
+    Circle(Circle),
+    Cross(Cross),
+    Union(Union),
+    Mask(Mask),
+}
+
+// #Bounds
+impl Circle {
+    pub fn bounds(&self) -> Space {
+        let Circle { centre: Point { x, y }, radius: r } = *self;
+        Rect::new_exact(x - r, y - r, x + r + 1, y + r + 1)
+    }
+}
+
+impl Cross {
+    pub fn bounds(&self) -> Space {
+        Rect::new_exact(self.hor.pos.x, self.ver.pos.y, self.hor.end.x, self.ver.end.y)
+    }
+}
+
+impl Union {
+    pub fn bounds(&self) -> Space {
+        let mut rects = self.0.iter();
+        let first = *rects.next().expect("union is not empty");
+        rects.fold(first, |a, b| Rect::new_exact(
+            a.pos.x.min(b.pos.x), a.pos.y.min(b.pos.y),
+            a.end.x.max(b.end.x), a.end.y.max(b.end.y),
+        ))
+    }
+}
+
+impl Mask {
+    /// A mask of the tiles in `bounds` that are `inside`.
+    pub fn new(bounds: Space, inside: impl Fn(Position) -> bool) -> Self {
+        Mask { bounds, tiles: bounds.into_iter().map(inside).collect() }
+    }
+
+    pub fn bounds(&self) -> Space {
+        self.bounds
+    }
+}
+
+impl Shape {
+    /// The smallest rectangle around the shape.
+    pub fn bounds(&self) -> Space {
+        match self {
+            Shape::Rect(rect) => *rect,
+            Shape::Circle(circle) => circle.bounds(),
+            Shape::Cross(cross) => cross.bounds(),
+            Shape::Union(union) => union.bounds(),
+            Shape::Mask(mask) => mask.bounds(),
+        }
+    }
+}
+
+// #Loading
+impl TryFrom<Vec<Space>> for Union {
+    type Error = String;
+
+    fn try_from(rects: Vec<Space>) -> Result<Self, String> {
+        if rects.is_empty() {
+            return Err("union has no rectangles".to_string());
+        }
+        Ok(Union(rects))
+    }
+}
+
+impl TryFrom<SavedMask> for Mask {
+    type Error = String;
+
+    /// A mask needs a tile for every position in its bounds, and at least
+    /// one of them inside.
+    fn try_from(SavedMask { bounds, tiles }: SavedMask) -> Result<Self, String> {
+        if tiles.len() != bounds.into_iter().count() {
+            return Err("mask tiles don't fit its bounds".to_string());
+        }
+        if !tiles.contains(&true) {
+            return Err("mask has no tiles".to_string());
+        }
+        Ok(Mask { bounds, tiles })
+    }
+}
+
+// #Areas
+impl Area for Circle {
+    fn contains(&self, p: Position) -> bool {
+        let Point { x, y } = p - self.centre;
+        x * x + y * y <= self.radius * self.radius + self.radius
+    }
+
+    fn middle(&self) -> Position {
+        self.centre
+    }
+}
+
+impl Area for Cross {
+    fn contains(&self, p: Position) -> bool {
+        self.hor.contains(p) || self.ver.contains(p)
+    }
+
+    /// The middle of where the bars cross.
+    fn middle(&self) -> Position {
+        Point { x: self.ver.middle().x, y: self.hor.middle().y }
+    }
+}
+
+impl Area for Union {
+    fn contains(&self, p: Position) -> bool {
+        self.0.iter().any(|rect| rect.contains(p))
+    }
+
+    /// The middle of the first rectangle.
+    fn middle(&self) -> Position {
+        self.0[0].middle()
+    }
+}
+
+impl Area for Mask {
+    fn contains(&self, p: Position) -> bool {
+        let Rect { pos, end } = self.bounds;
+        self.bounds.contains(p) &&
+            self.tiles[((p.y - pos.y) * (end.x - pos.x) + p.x - pos.x) as usize]
+    }
+
+    /// The tile closest to the middle of the bounds.
+    fn middle(&self) -> Position {
+        let middle = self.bounds.middle();
+        tiles(self.bounds, self).into_iter()
+            .min_by_key(|p| { let Point { x, y } = *p - middle; x.abs() + y.abs() })
+            .expect("mask is not empty")
+    }
+}
+
+impl Area for Shape {
+    fn contains(&self, p: Position) -> bool {
+        match self {
+            Shape::Rect(rect) => rect.contains(p),
+            Shape::Circle(circle) => circle.contains(p),
+            Shape::Cross(cross) => cross.contains(p),
+            Shape::Union(union) => union.contains(p),
+            Shape::Mask(mask) => mask.contains(p),
+        }
+    }
+
+    fn middle(&self) -> Position {
+        match self {
+            Shape::Rect(rect) => rect.middle(),
+            Shape::Circle(circle) => circle.middle(),
+            Shape::Cross(cross) => cross.middle(),
+            Shape::Union(union) => union.middle(),
+            Shape::Mask(mask) => mask.middle(),
+        }
+    }
+}
+
+// #Point iterators
+/// The tiles of `area` within `bounds`, row by row.
+fn tiles(bounds: Space, area: &impl Area) -> Vec<Position> {
+    bounds.into_iter().filter(|p| area.contains(*p)).collect()
+}
+
+/// The unbroken runs of `tiles` in each row. The tiles go row by row.
+fn strips(tiles: Vec<Position>) -> vec::IntoIter<Strip> {
+    let mut strips: Vec<Strip> = Vec::new();
+    for p in tiles {
+        match strips.last_mut() {
+            Some(strip) if strip.next.y == p.y && strip.end == p.x => strip.end += 1,
+            _ => strips.push(Strip { next: p, end: p.x + 1 }),
+        }
+    }
+    strips.into_iter()
+}
+
+macro_rules! impl_points {
+    ($($shape:ty),*) => {$(
+        impl IntoIterator for $shape {
+            type Item = Position;
+            type IntoIter = vec::IntoIter<Position>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                (&self).into_iter()
+            }
+        } impl IntoIterator for &$shape {
+            type Item = Position;
+            type IntoIter = vec::IntoIter<Position>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                tiles(self.bounds(), self).into_iter()
+            }
+        }
+
+        impl Points for $shape {
+            type StripsIter = vec::IntoIter<Strip>;
+
+            fn strips(self) -> Self::StripsIter {
+                strips(self.into_iter().collect())
+            }
+        }
+    )*};
+}
+impl_points!(Circle, Cross, Union, Mask, Shape);
@@ -0,0 +1,131 @@
+use std::{fmt, io::{Write, Result}};
+
+use crossterm::{queue,
+    cursor::MoveTo,
+    terminal::{Clear, ClearType},
+    style::{Print, SetForegroundColor, Color, ResetColor}};
+
+/// Something the game can be drawn on.
+pub trait Renderer {
+    /// Puts a character at a screen position.
+    fn put(&mut self, x: u16, y: u16, c: char) -> Result<()>;
+
+    /// Sets the colour of the following characters. `None` resets it.
+    fn colour(&mut self, colour: Option<Color>) -> Result<()>;
+
+    /// Clears the whole screen.
+    fn clear(&mut self) -> Result<()>;
+
+    fn flush(&mut self) -> Result<()>;
+
+    /// Puts a line of text starting at a screen position.
+    fn print(&mut self, x: u16, y: u16, text: &str) -> Result<()> {
+        for (c, x) in text.chars().zip(x..) {
+            self.put(x, y, c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Draws to a terminal through crossterm.
+pub struct Terminal<W: Write>(pub W);
+
+impl<W: Write> Renderer for Terminal<W> {
+    fn put(&mut self, x: u16, y: u16, c: char) -> Result<()> {
+        queue!(self.0, MoveTo(x, y), Print(c))
+    }
+
+    fn colour(&mut self, colour: Option<Color>) -> Result<()> {
+        match colour {
+            Some(colour) => queue!(self.0, SetForegroundColor(colour)),
+            None => queue!(self.0, ResetColor),
+        }
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        queue!(self.0, Clear(ClearType::All))
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.0.flush()
+    }
+
+    fn print(&mut self, x: u16, y: u16, text: &str) -> Result<()> {
+        queue!(self.0, MoveTo(x, y), Print(text))
+    }
+}
+
+/// Draws to a grid of cells in memory, so frames can be inspected.
+pub struct Grid {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+    colour: Option<Color>,
+} impl Grid {
+    pub fn new(width: u16, height: u16) -> Self {
+        let cells = vec![Cell::default(); usize::from(width) * usize::from(height)];
+        Self { width, height, cells, colour: None }
+    }
+
+    pub fn get(&self, x: u16, y: u16) -> Option<Cell> {
+        self.index(x, y).map(|i| self.cells[i])
+    }
+
+    /// The character at a screen position, or a space outside the grid.
+    pub fn char(&self, x: u16, y: u16) -> char {
+        self.get(x, y).map_or(' ', |cell| cell.c)
+    }
+
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(usize::from(y) * usize::from(self.width) + usize::from(x))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Cell {
+    pub c: char,
+    pub colour: Option<Color>,
+} impl Default for Cell {
+    fn default() -> Self {
+        Self { c: ' ', colour: None }
+    }
+}
+
+impl Renderer for Grid {
+    /// Characters outside the grid are dropped.
+    fn put(&mut self, x: u16, y: u16, c: char) -> Result<()> {
+        if let Some(i) = self.index(x, y) {
+            self.cells[i] = Cell { c, colour: self.colour };
+        }
+        Ok(())
+    }
+
+    fn colour(&mut self, colour: Option<Color>) -> Result<()> {
+        self.colour = colour;
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.cells.fill(Cell::default());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The characters of the grid, one line per row, without trailing spaces.
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(usize::from(self.width).max(1)) {
+            let line: String = row.iter().map(|cell| cell.c).collect();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+use std::{fs, path::Path, io::{Result, Error, ErrorKind}};
+
+use petgraph::{Graph, Undirected};
+use serde::{Serialize, Serializer, Deserialize};
+use serde_json::{Value, json};
+
+use super::{GameWorld, Map, MapElem, TileMap, TileKind, Stairs, Position, points::Area};
+
+/// The version of the save file format written by this build.
+const VERSION: u64 = 5;
+/// Turn older save files into the next version, starting from version 1.
+/// A save file from version `v` goes through `MIGRATIONS[v - 1..]`.
+const MIGRATIONS: [fn(&mut Value); VERSION as usize - 1] = [one_level_to_dungeon, open_doors, bent_halls, shaped_rooms];
+
+/// Version 1 had a single level with no stairs. It becomes the first level
+/// of a dungeon as big as the saved map, with the down stairs in the corner
+/// of the first room. The size the map was generated at wasn't saved, and
+/// every size was partitioned up to 6 times. A save without any map gets no
+/// dungeon, and fails to load.
+fn one_level_to_dungeon(save: &mut Value) {
+    let seed = save["seed"].clone();
+    let world = &mut save["world"];
+    let graph = world["map"].take();
+    let room = graph["nodes"].as_array()
+        .and_then(|nodes| nodes.iter().find_map(|node| node.get("Room")));
+    let stairs = match room {
+        Some(room) => json!([[room["pos"], "Down"]]),
+        None => json!([]),
+    };
+    if let Some((width, height)) = map_size(&graph) {
+        world["dungeon"] = json!({
+            "seed": seed, "width": width, "height": height, "max_partition": 6,
+        });
+    }
+    world["map"] = json!({ "graph": graph, "stairs": stairs });
+    world["levels"] = json!({});
+}
+
+/// The width and height a version 1 map fits in, going by the furthest
+/// wall, hall and door tiles. Maps start at 1, 1.
+fn map_size(graph: &Value) -> Option<(i64, i64)> {
+    let point = |p: &Value| Some((p["x"].as_i64()?, p["y"].as_i64()?));
+    let mut tiles = Vec::new();
+    for node in graph["nodes"].as_array()? {
+        if let Some(room) = node.get("Room") {
+            tiles.push(point(&room["end"])?);
+        } else if let Some(door) = node.get("Door") {
+            tiles.push(point(door)?);
+        } else if let Some(hall) = node.get("Hall") {
+            let (x, y) = point(&hall["pos"])?;
+            tiles.push((x, y));
+            match (hall["end"]["X"].as_i64(), hall["end"]["Y"].as_i64()) {
+                (Some(end), _) => tiles.push((end - 1, y)),
+                (_, Some(end)) => tiles.push((x, end - 1)),
+                _ => return None,
+            }
+        }
+    }
+    let width = tiles.iter().map(|(x, _)| *x).max()?;
+    let height = tiles.iter().map(|(_, y)| *y).max()?;
+    Some((width, height))
+}
+
+/// Applies a migration to the current level and to every stored one.
+fn for_each_level(save: &mut Value, f: fn(&mut Value)) {
+    let world = &mut save["world"];
+    f(world);
+    if let Some(levels) = world["levels"].as_object_mut() {
+        levels.values_mut().for_each(f);
+    }
+}
+
+/// Version 2 had doors without a state, which could be walked through.
+/// They become open doors, on the map and in the explored tiles.
+fn open_doors(save: &mut Value) {
+    for_each_level(save, |level| {
+        if let Some(nodes) = level["map"]["graph"]["nodes"].as_array_mut() {
+            for door in nodes.iter_mut().filter_map(|node| node.get_mut("Door")) {
+                *door = json!([door.take(), "Open"]);
+            }
+        }
+        if let Some(tiles) = level["explored"].as_array_mut() {
+            for tile in tiles.iter_mut().filter(|tile| tile[1] == "Door") {
+                tile[1] = json!({ "Door": "Open" });
+            }
+        }
+    });
+}
+
+/// Version 3 had straight halls only. Each becomes a hall of one line.
+fn bent_halls(save: &mut Value) {
+    for_each_level(save, |level| {
+        if let Some(nodes) = level["map"]["graph"]["nodes"].as_array_mut() {
+            for hall in nodes.iter_mut().filter_map(|node| node.get_mut("Hall")) {
+                *hall = json!([hall.take()]);
+            }
+        }
+    });
+}
+
+/// Version 4 had rectangular rooms only. Each keeps its rectangle as its shape.
+fn shaped_rooms(save: &mut Value) {
+    for_each_level(save, |level| {
+        if let Some(nodes) = level["map"]["graph"]["nodes"].as_array_mut() {
+            for room in nodes.iter_mut().filter_map(|node| node.get_mut("Room")) {
+                *room = json!({ "Rect": room.take() });
+            }
+        }
+    });
+}
+
+impl GameWorld {
+    /// Writes the whole game to `path`, together with the `seed` it was
+    /// generated from.
+    pub fn save(&self, seed: u64, path: &Path) -> Result<()> {
+        let world = serde_json::to_value(self).map_err(Error::other)?;
+        let save = json!({ "version": VERSION, "seed": seed, "world": world });
+        fs::write(path, save.to_string())
+    }
+
+    /// Reads a game written by `save`, and the seed it was generated from.
+    /// Saves from older versions are migrated. Anything else that isn't a
+    /// valid save gives an `InvalidData` error.
+    pub fn load(path: &Path) -> Result<(GameWorld, u64)> {
+        let mut save: Value = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| invalid(format!("not a save file: {e}")))?;
+
+        let version = save["version"].as_u64()
+            .ok_or_else(|| invalid("save file has no version".to_string()))?;
+        if version == 0 || version > VERSION {
+            return Err(invalid(format!(
+                "save file version {version} is not supported, expected at most {VERSION}"
+            )));
+        }
+        for migrate in &MIGRATIONS[version as usize - 1..] {
+            migrate(&mut save);
+        }
+
+        let seed = save["seed"].as_u64()
+            .ok_or_else(|| invalid("save file has no seed".to_string()))?;
+        let world: GameWorld = serde_json::from_value(save["world"].take())
+            .map_err(|e| invalid(format!("broken save file: {e}")))?;
+        world.check().map_err(|e| invalid(format!("broken save file: {e}")))?;
+        Ok((world, seed))
+    }
+
+    /// Checks that everything stands in an area of the map on every level,
+    /// and that the stairs lead to levels there are, so a broken save can't
+    /// make the game panic later on.
+    fn check(&self) -> std::result::Result<(), String> {
+        let current = (self.depth, &self.map, &self.monsters, &self.items);
+        let levels = self.levels.iter()
+            .map(|(depth, level)| (*depth, &level.map, &level.monsters, &level.items));
+        for (depth, map, monsters, items) in levels.chain([current]) {
+            let stands_in = |pos, area| {
+                map.node_weight(area).is_some_and(|a: &MapElem| a.contains(pos))
+            };
+            if depth == self.depth && !stands_in(self.player.pos, self.player.area) {
+                return Err("player is outside the map".to_string());
+            }
+            if monsters.iter().any(|m| !stands_in(m.pos, m.area)) {
+                return Err(format!("monster is outside the map on level {depth}"));
+            }
+            if items.iter().any(|i| !map.areas.contains_key(&i.pos)) {
+                return Err(format!("item is outside the map on level {depth}"));
+            }
+
+            let has = |stairs| map.stairs.iter()
+                .any(|(p, s)| *s == stairs && map.contains_tile(*p).is_some());
+            if depth == 0 || !has(Stairs::Down) || (depth > 1 && !has(Stairs::Up)) {
+                return Err(format!("level {depth} is missing stairs"));
+            }
+            if depth == 1 && map.stairs.values().any(|s| *s == Stairs::Up) {
+                return Err("level 1 has stairs up".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// A map as it is saved. The tile lookups are built again from the graph.
+#[derive(Serialize, Deserialize)]
+pub(super) struct SavedMap<G = Graph<MapElem, (), Undirected>, S = Vec<(Position, Stairs)>> {
+    graph: G,
+    stairs: S,
+}
+
+impl Serialize for Map {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let stairs: Vec<_> = self.stairs.iter().collect();
+        SavedMap { graph: &self.graph, stairs }.serialize(serializer)
+    }
+}
+
+impl From<SavedMap> for Map {
+    fn from(SavedMap { graph, stairs }: SavedMap) -> Self {
+        let mut map = Map { graph, stairs: stairs.into_iter().collect(), ..Map::new() };
+        for index in map.graph.node_indices() {
+            map.index_tiles(index);
+        }
+        map
+    }
+}
+
+impl Serialize for TileMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl From<Vec<(Position, TileKind)>> for TileMap {
+    fn from(tiles: Vec<(Position, TileKind)>) -> Self {
+        TileMap(tiles.into_iter().collect())
+    }
+}
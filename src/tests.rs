@@ -0,0 +1,757 @@
+use super::*;
+use render::{Grid, Cell};
+use write::Viewport;
+use crossterm::style::Color;
+
+#[test]
+fn explored_tiles_are_remembered() {
+    let mut world = custom_world();
+    let start = world.player.pos;
+    let seen: Vec<_> = world.explored.keys().copied().collect();
+    assert!(seen.contains(&start));
+
+    for _ in 0..3 {
+        let next = world.next(KeyCode::Right);
+        world.update(next);
+    }
+    for _ in 0..6 {
+        let next = world.next(KeyCode::Left);
+        world.update(next);
+    }
+
+    for pos in seen {
+        assert!(world.explored.contains_key(&pos));
+    }
+    assert!(world.explored.values().all(|tile| !tile.is_obj()));
+}
+
+#[test]
+fn frame_snapshot() {
+    let world = custom_world();
+    let mut grid = Grid::new(61, 19);
+    world.print(&mut grid, Next::default()).unwrap();
+
+    assert_eq!(include_str!("tests/custom_world.txt"), grid.to_string());
+}
+
+#[test]
+fn moving_fades_old_tiles() {
+    let mut world = custom_world();
+    let mut grid = Grid::new(61, 19);
+    world.print(&mut grid, Next::default()).unwrap();
+
+    let Point { x, y } = world.player.pos;
+    let (x, y) = (x as u16, y as u16);
+    assert_eq!(Some(Cell { c: '@', colour: None }), grid.get(x, y));
+    assert_eq!(Some(Color::DarkGrey), grid.get(0, 0).unwrap().colour);
+
+    let next = world.next(KeyCode::Left);
+    world.print(&mut grid, next).unwrap();
+    world.update(next);
+
+    assert_eq!(Some(Cell { c: '@', colour: None }), grid.get(x - 1, y));
+    assert_eq!(Some(Cell { c: '.', colour: None }), grid.get(x, y));
+}
+
+#[test]
+fn viewport_follows_player() {
+    let view = Viewport::new(Rect::new(1, 1, 59, 17));
+    assert_eq!(Some((1, 1)), view.to_screen(Point { x: 1, y: 1 }));
+    assert_eq!(None, view.to_screen(Point { x: 60, y: 1 }));
+
+    let far = Point { x: 200, y: 100 };
+    let view = view.follow(far);
+    assert_eq!(Some((30, 9)), view.to_screen(far));
+    assert_eq!(None, view.to_screen(Point { x: 1, y: 1 }));
+    assert_eq!(view, view.follow(far + X(5)));
+    assert_ne!(view, view.follow(far + X(30)));
+}
+
+#[test]
+fn player_stays_in_frame() {
+    use rand::prelude::*;
+    let mut rng = StdRng::seed_from_u64(0);
+    let keys = [KeyCode::Left, KeyCode::Right, KeyCode::Up, KeyCode::Down];
+
+    for seed in 0..10 {
+        let mut world = generate_world(150, 50, 6, seed);
+        let mut grid = Grid::new(61, 19);
+        world.print(&mut grid, Next::default()).unwrap();
+
+        for _ in 0..300 {
+            let next = world.next(*keys.choose(&mut rng).unwrap());
+            world.print(&mut grid, next).unwrap();
+            world.update(next);
+
+            let frame = grid.to_string();
+            assert_eq!(1, frame.matches('@').count(), "{frame}");
+        }
+    }
+}
+
+#[test]
+fn resize_frame() {
+    let mut world = custom_world();
+    let mut grid = Grid::new(44, 17);
+    world.resize(44, 17);
+    world.print(&mut grid, Next::default()).unwrap();
+    world.print_hud(&mut grid).unwrap();
+
+    assert_eq!('╔', grid.char(0, 0));
+    assert_eq!('╗', grid.char(29, 0));
+    assert_eq!('╝', grid.char(29, 10));
+    assert_eq!('╗', grid.char(43, 0));
+    assert_eq!('╔', grid.char(0, 11));
+    assert_eq!('╝', grid.char(43, 15));
+    assert_eq!(' ', grid.char(0, 16));
+    assert_eq!(1, grid.to_string().matches('@').count());
+
+    assert!(write::fits(44, 17));
+    assert!(!write::fits(30, 12));
+    write::print_too_small(&mut grid, 10, 12).unwrap();
+    assert!(grid.to_string().starts_with("Terminal too small"));
+}
+
+#[test]
+fn monsters_take_turns() {
+    for seed in 0..20 {
+        let mut world = generate_world(80, 30, 4, seed);
+        let mut grid = Grid::new(81, 32);
+        world.resize(81, 32);
+        world.print(&mut grid, Next::default()).unwrap();
+
+        for _ in 0..100 {
+            for i in world.monsters() {
+                let next = world.next_monster(i);
+                world.print(&mut grid, next).unwrap();
+                world.update(next);
+            }
+            let mut taken = vec![world.player.pos];
+            for monster in &world.monsters {
+                assert!(world.map[monster.area].contains(monster.pos));
+                assert!(!taken.contains(&monster.pos));
+                taken.push(monster.pos);
+            }
+        }
+
+        let visible = world.visible_tiles(world.player.pos);
+        let mut fresh = Grid::new(81, 32);
+        world.print(&mut fresh, Next::default()).unwrap();
+        for monster in &world.monsters {
+            if let Some((x, y)) = world.view.to_screen(monster.pos) {
+                assert_eq!(fresh.get(x, y), grid.get(x, y));
+                if visible.contains_key(&monster.pos) {
+                    assert_eq!(char::from(monster.kind), grid.char(x, y));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn bump_to_attack() {
+    let mut world = custom_world();
+    let pos = world.player.pos + X(1);
+    world.monsters.push(Monster::new(MonsterKind::Goblin, pos, world.player.area));
+
+    let mut grid = Grid::new(61, 19);
+    world.print(&mut grid, Next::default()).unwrap();
+    let (x, y) = world.view.to_screen(pos).unwrap();
+    assert_eq!('g', grid.char(x, y));
+
+    // Goblin: 6 hp, 1 defence. Player: 4 attack.
+    for hp in [3, 0] {
+        let next = world.next(KeyCode::Right);
+        assert!(next.takes_turn());
+        world.print(&mut grid, next).unwrap();
+        world.update(next);
+        assert_eq!(hp, world.monsters.first().map_or(0, |m| m.stats.hp));
+    }
+    assert!(world.monsters.is_empty());
+    assert_eq!(1, world.kills);
+    assert_eq!('.', grid.char(x, y));
+
+    let next = world.next(KeyCode::Right);
+    world.update(next);
+    assert_eq!(pos, world.player.pos);
+}
+
+#[test]
+fn monsters_kill_player() {
+    let mut world = custom_world();
+    let pos = world.player.pos + X(1);
+    world.monsters.push(Monster::new(MonsterKind::Rat, pos, world.player.area));
+
+    while !world.is_over() {
+        let next = world.attack(Actor::Monster(0), Actor::Player);
+        assert!(!next.takes_turn());
+        world.update(next);
+    }
+    assert_eq!(0, world.player.stats.hp);
+
+    let mut grid = Grid::new(61, 19);
+    world.print_results(&mut grid).unwrap();
+    assert!(grid.to_string().contains("You died."));
+}
+
+#[test]
+fn monsters_find_player() {
+    let mut world = custom_world();
+    let start = Point { x: 31, y: 13 };
+    let area = world.map.areas[&start];
+    world.monsters.push(Monster::new(MonsterKind::Rat, start, area));
+
+    for _ in 0..50 {
+        let Some(p) = world.step_towards(0, world.player.pos, world.player.area) else {
+            break;
+        };
+        if p == world.player.pos {
+            break;
+        }
+        let area = world.step(world.monsters[0].area, p).unwrap();
+        world.update(Next(Change::Monster(0, p, area)));
+    }
+    let Point { x, y } = world.monsters[0].pos - world.player.pos;
+    assert_eq!(1, x.abs() + y.abs());
+}
+
+#[test]
+fn monsters_chase_player() {
+    let mut world = custom_world();
+    let pos = world.player.pos + X(2);
+    world.monsters.push(Monster::new(MonsterKind::Goblin, pos, world.player.area));
+    assert!(world.sees_player(0));
+
+    let next = world.next_monster(0);
+    world.update(next);
+    assert_eq!(world.player.pos + X(1), world.monsters[0].pos);
+
+    let next = world.next_monster(0);
+    assert!(matches!(next, Next(Change::Attack(Actor::Monster(0), Actor::Player, _))));
+}
+
+#[test]
+fn fast_monsters_act_more_often() {
+    let mut world = custom_world();
+    let area = world.player.area;
+    for kind in [MonsterKind::Rat, MonsterKind::Goblin, MonsterKind::Zombie] {
+        world.monsters.push(Monster::new(kind, Point { x: 0, y: 0 }, area));
+    }
+
+    let mut actions = [0; 3];
+    for _ in 0..12 {
+        let next = world.next(KeyCode::Char('.'));
+        assert!(next.takes_turn());
+        world.update(next);
+
+        while let Actor::Monster(i) = world.next_actor() {
+            actions[i] += 1;
+            world.update(Next(Change::Wait(Actor::Monster(i))));
+        }
+    }
+    // The monsters act after the player, so they have had 11 ticks of energy.
+    assert_eq!(12, world.turns);
+    assert_eq!([16, 11, 5], actions);
+}
+
+#[test]
+fn attacks_cost_more_than_steps() {
+    let mut world = custom_world();
+    let pos = world.player.pos + X(1);
+    world.monsters.push(Monster::new(MonsterKind::Zombie, pos, world.player.area));
+
+    assert_eq!(Actor::Player, world.next_actor());
+    let next = world.next(KeyCode::Right);
+    assert!(next.cost() > world.next(KeyCode::Left).cost());
+    world.update(next);
+    assert!(world.player.energy < 0);
+}
+
+#[test]
+fn pick_up_and_drop() {
+    let mut world = custom_world();
+    let pos = world.player.pos + X(1);
+    world.items.push(Item { kind: ItemKind::HealingPotion, pos });
+
+    let mut grid = Grid::new(61, 19);
+    world.print(&mut grid, Next::default()).unwrap();
+    let (x, y) = world.view.to_screen(pos).unwrap();
+    assert_eq!('!', grid.char(x, y));
+
+    assert!(!world.next(KeyCode::Char('g')).takes_turn());
+    for key in [KeyCode::Right, KeyCode::Char('g'), KeyCode::Left] {
+        let next = world.next(key);
+        assert!(next.takes_turn());
+        world.print(&mut grid, next).unwrap();
+        world.update(next);
+    }
+    assert!(world.items.is_empty());
+    assert_eq!([ItemKind::HealingPotion], world.inventory());
+    assert_eq!('.', grid.char(x, y));
+
+    assert!(!world.drop_item(KeyCode::Char('b')).takes_turn());
+    let next = world.drop_item(KeyCode::Char('a'));
+    world.update(next);
+    assert!(world.inventory().is_empty());
+    assert_eq!(Some(0), world.item_at(world.player.pos));
+
+    let next = world.next(KeyCode::Right);
+    world.print(&mut grid, next).unwrap();
+    world.update(next);
+    let (x, y) = world.view.to_screen(world.player.pos - X(1)).unwrap();
+    assert_eq!('!', grid.char(x, y));
+}
+
+#[test]
+fn inventory_is_limited() {
+    let mut world = custom_world();
+    let pos = world.player.pos;
+    for _ in 0..=INVENTORY_SIZE {
+        world.items.push(Item { kind: ItemKind::MappingScroll, pos });
+    }
+    while world.next(KeyCode::Char('g')).takes_turn() {
+        world.update(world.next(KeyCode::Char('g')));
+    }
+    assert_eq!(INVENTORY_SIZE, world.inventory().len());
+    assert_eq!(1, world.items.len());
+
+    let mut grid = Grid::new(61, 19);
+    world.print_inventory(&mut grid, "Inventory").unwrap();
+    let screen = grid.to_string();
+    assert!(screen.contains("Inventory (10/10)"));
+    assert!(screen.contains("j) mapping scroll"));
+}
+
+#[test]
+fn items_draw_themselves() {
+    let mut world = custom_world();
+    let pos = world.player.pos + X(1);
+    world.items.push(Item { kind: ItemKind::Sword, pos });
+
+    let mut grid = Grid::new(61, 19);
+    world.print(&mut grid, Next::default()).unwrap();
+    let (x, y) = world.view.to_screen(pos).unwrap();
+    let ItemDef { glyph, colour, .. } = ItemKind::Sword.def();
+    assert_eq!(Some(Cell { c: glyph, colour: Some(colour) }), grid.get(x, y));
+}
+
+#[test]
+fn equipment_changes_stats() {
+    let mut world = custom_world();
+    let base = world.stats(Actor::Player);
+    world.player.inventory = vec![ItemKind::Dagger, ItemKind::ChainMail, ItemKind::Sword];
+
+    for _ in 0..2 {
+        world.update(world.use_item(KeyCode::Char('a')));
+    }
+    let stats = world.stats(Actor::Player);
+    assert_eq!(base.attack + 1, stats.attack);
+    assert_eq!(base.defence + 3, stats.defence);
+    assert_eq!([ItemKind::Sword], world.inventory());
+
+    let next = world.use_item(KeyCode::Char('a'));
+    assert!(next.takes_turn());
+    world.update(next);
+    assert_eq!(base.attack + 3, world.stats(Actor::Player).attack);
+    assert_eq!([ItemKind::Dagger], world.inventory());
+    assert_eq!((Slot::Weapon, Some(ItemKind::Sword)), world.equipment()[0]);
+}
+
+#[test]
+fn consumables_have_effects() {
+    let mut world = custom_world();
+    world.player.inventory = vec![
+        ItemKind::HealingPotion, ItemKind::MappingScroll, ItemKind::TeleportScroll,
+    ];
+    world.player.stats.hp = 10;
+
+    world.update(world.use_item(KeyCode::Char('a')));
+    assert_eq!(18, world.player.stats.hp);
+
+    world.update(world.use_item(KeyCode::Char('a')));
+    assert_eq!(world.map.all_tiles().len(), world.explored.len());
+
+    for _ in 0..10 {
+        world.player.inventory.push(ItemKind::TeleportScroll);
+        world.update(world.use_item(KeyCode::Char('a')));
+        assert!(world.map[world.player.area].contains(world.player.pos));
+        assert!(matches!(world.map[world.player.area], MapElem::Room(_)));
+    }
+    assert_eq!([ItemKind::TeleportScroll], world.inventory());
+}
+
+#[test]
+fn kills_give_levels() {
+    let mut world = custom_world();
+    let base = world.stats(Actor::Player);
+    world.player.stats.hp = 10;
+
+    world.gain_xp(9);
+    assert_eq!(1, world.player.character.level);
+    world.gain_xp(MonsterKind::Zombie.xp() + 20);
+    assert_eq!(3, world.player.character.level);
+    assert_eq!(7, world.player.character.xp);
+
+    let stats = world.stats(Actor::Player);
+    assert_eq!(base.attack + 2, stats.attack);
+    assert_eq!(base.defence + 1, stats.defence);
+    assert_eq!((20, 30), (stats.hp, stats.max_hp));
+
+    let pos = world.player.pos + X(1);
+    world.monsters.push(Monster::new(MonsterKind::Rat, pos, world.player.area));
+    world.update(world.next(KeyCode::Right));
+    assert_eq!(9, world.player.character.xp);
+
+    let mut grid = Grid::new(80, 20);
+    world.resize(80, 20);
+    world.print_hud(&mut grid).unwrap();
+    let hud = grid.to_string();
+    for line in ["Level 3", "XP    9/30", "HP    20/30", "Str   6", "Dex   4"] {
+        assert!(hud.contains(line), "{hud}");
+    }
+}
+
+#[test]
+fn messages_are_logged() {
+    let mut world = custom_world();
+    let pos = world.player.pos + X(1);
+    world.monsters.push(Monster::new(MonsterKind::Goblin, pos, world.player.area));
+
+    world.update(world.attack(Actor::Monster(0), Actor::Player));
+    world.update(world.next(KeyCode::Right));
+    world.update(world.next(KeyCode::Right));
+    assert_eq!(
+        ["The goblin hits you.", "You hit the goblin.", "You kill the goblin."],
+        world.messages(),
+    );
+
+    let mut grid = Grid::new(80, 24);
+    world.resize(80, 24);
+    world.print_hud(&mut grid).unwrap();
+    let screen = grid.to_string();
+    for line in ["The goblin hits you.", "You kill the goblin.", "Depth 1", "Turn  2"] {
+        assert!(screen.contains(line), "{screen}");
+    }
+
+    for i in 0..30 {
+        world.log(format!("Message {i}"));
+    }
+    let mut grid = Grid::new(40, 12);
+    world.print_messages(&mut grid, 40, 12, 5).unwrap();
+    let screen = grid.to_string();
+    assert!(screen.contains("Messages"));
+    assert!(screen.contains("Message 24") && !screen.contains("Message 25"));
+    assert!(screen.contains("Message 15") && !screen.contains("Message 14"));
+}
+
+#[test]
+fn menu_selection() {
+    let mut menu = menu::Menu::new("Rogue", &["New game", "Continue", "Quit"]);
+    assert_eq!(None, menu.select(KeyCode::Up));
+    assert_eq!(2, menu.selected());
+    assert_eq!(None, menu.select(KeyCode::Down));
+    assert_eq!(None, menu.select(KeyCode::Down));
+    assert_eq!(None, menu.select(KeyCode::Char('x')));
+    assert_eq!(Some(1), menu.select(KeyCode::Enter));
+
+    let mut grid = Grid::new(40, 12);
+    menu.print(&mut grid, 40, 12).unwrap();
+    let screen = grid.to_string();
+    assert!(screen.contains("Rogue"));
+    assert!(screen.contains("> Continue"));
+    assert!(screen.contains("  New game"));
+    assert_eq!(1, screen.matches('╔').count());
+}
+
+#[test]
+fn save_and_load() {
+    let path = std::env::temp_dir().join("rogue_save_and_load.save");
+    let mut world = generate_world(80, 30, 4, 7);
+    for key in [KeyCode::Left, KeyCode::Up, KeyCode::Char('.')] {
+        world.update(world.next(key));
+    }
+    world.player.inventory.push(ItemKind::Sword);
+    world.save(7, &path).unwrap();
+
+    let (mut loaded, seed) = GameWorld::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(7, seed);
+    assert_eq!(world.player.pos, loaded.player.pos);
+    assert_eq!(world.player.area, loaded.player.area);
+    assert_eq!(world.inventory(), loaded.inventory());
+    assert_eq!(world.turns, loaded.turns);
+    assert_eq!(world.map.edge_count(), loaded.map.edge_count());
+    assert!(world.map.all_tiles().iter().all(|(p, t)| loaded.map.get_tile(*p) == Some(*t)));
+    assert!(world.explored.iter().all(|(p, t)| loaded.explored.get(p) == Some(t)));
+    assert_eq!(world.explored.len(), loaded.explored.len());
+    assert_eq!(world.items.len(), loaded.items.len());
+
+    for _ in 0..20 {
+        for i in world.monsters() {
+            let next = world.next_monster(i);
+            world.update(next);
+            let next = loaded.next_monster(i);
+            loaded.update(next);
+            assert!(world.monsters[i].pos == loaded.monsters[i].pos);
+        }
+    }
+}
+
+#[test]
+fn broken_saves_give_errors() {
+    let path = std::env::temp_dir().join("rogue_broken_saves.save");
+    let world = custom_world();
+    world.save(0, &path).unwrap();
+    let save = std::fs::read_to_string(&path).unwrap();
+
+    let outside = save.replace(
+        &format!("\"pos\":{{\"x\":{},", world.player.pos.x),
+        "\"pos\":{\"x\":-100,",
+    );
+    assert_ne!(save, outside);
+    let first = |kind, change: fn(&mut serde_json::Value)| {
+        let mut save: serde_json::Value = serde_json::from_str(&save).unwrap();
+        let nodes = save["world"]["map"]["graph"]["nodes"].as_array_mut().unwrap();
+        change(nodes.iter_mut().find_map(|node| node.get_mut(kind)).unwrap());
+        save.to_string()
+    };
+    for broken in [
+        first("Room", |room| {
+            *room = serde_json::json!({ "Mask": { "bounds": room["Rect"], "tiles": [true] } });
+        }),
+        first("Room", |room| *room = serde_json::json!({ "Union": [] })),
+        first("Hall", |hall| *hall = serde_json::json!([])),
+        "not json".to_string(),
+        "{}".to_string(),
+        save.replace("\"version\":5", "\"version\":99"),
+        save.replace("\"version\":5", "\"version\":0"),
+        save.replace("\"Down\"", "\"Up\""),
+        save[..save.len() / 2].to_string(),
+        save.replace("\"turns\":0", "\"turns\":-1"),
+        outside,
+    ] {
+        std::fs::write(&path, broken).unwrap();
+        let error = GameWorld::load(&path).err().unwrap();
+        assert_eq!(std::io::ErrorKind::InvalidData, error.kind());
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn old_saves_are_migrated() {
+    let path = std::env::temp_dir().join("rogue_old_saves.save");
+    let world = custom_world();
+    world.save(5, &path).unwrap();
+
+    let mut save: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    save["version"] = 1.into();
+    let old = save["world"].as_object_mut().unwrap();
+    let mut graph = old["map"]["graph"].take();
+    for node in graph["nodes"].as_array_mut().unwrap() {
+        if let Some(door) = node.get_mut("Door") {
+            *door = door[0].take();
+        }
+        if let Some(hall) = node.get_mut("Hall") {
+            *hall = hall[0].take();
+        }
+        if let Some(room) = node.get_mut("Room") {
+            *room = room["Rect"].take();
+        }
+    }
+    for tile in old["explored"].as_array_mut().unwrap() {
+        if tile[1].get("Door").is_some() {
+            tile[1] = "Door".into();
+        }
+    }
+    old.insert("map".to_string(), graph);
+    old.remove("levels");
+    old.remove("dungeon");
+    std::fs::write(&path, save.to_string()).unwrap();
+
+    let (loaded, seed) = GameWorld::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(5, seed);
+    assert_eq!(5, loaded.dungeon.seed);
+    // The custom map fits in 32 x 14, not the medium size every old save used to get.
+    assert_eq!((32, 14), (loaded.dungeon.width, loaded.dungeon.height));
+    let deeper = map::generate_level(&loaded.dungeon, 2).map.all_tiles();
+    assert!(deeper.keys().all(|p| p.x <= 32 && p.y <= 14));
+    let (pos, stairs) = loaded.map.stairs.iter().next().unwrap();
+    assert_eq!(Stairs::Down, *stairs);
+    assert!(loaded.map.contains_tile(*pos).is_some_and(|t| t.is_room()));
+    assert_eq!(world.player.pos, loaded.player.pos);
+    assert!(loaded.map.node_weights().all(MapElem::is_passable));
+    assert!(loaded.explored.values().any(|t| *t == TileKind::Door(Door::Open)));
+}
+
+#[test]
+fn stairs_between_levels() {
+    let mut world = generate_world(60, 30, 4, 3);
+    let down = |world: &GameWorld| world.map.stairs.iter()
+        .find(|(_, s)| **s == Stairs::Down)
+        .map(|(p, _)| *p)
+        .unwrap();
+
+    assert!(!world.next(KeyCode::Char('>')).takes_turn());
+    let first = down(&world);
+    world.player.pos = first;
+    world.player.area = world.map.areas[&first];
+    world.items.push(Item { kind: ItemKind::Dagger, pos: first });
+    let explored = world.explored.len();
+    let monsters = world.monsters.len();
+
+    let next = world.next(KeyCode::Char('>'));
+    assert!(next.takes_turn());
+    world.update(next);
+    assert_eq!(2, world.depth());
+    assert_eq!(Some(&Stairs::Up), world.map.stairs.get(&world.player.pos));
+    assert!(world.map[world.player.area].contains(world.player.pos));
+    let second = world.player.pos;
+    let second_down = down(&world);
+
+    world.update(world.next(KeyCode::Char('<')));
+    assert_eq!(1, world.depth());
+    assert_eq!(first, world.player.pos);
+    let top = world.item_at(first).unwrap();
+    assert_eq!(ItemKind::Dagger, world.items[top].kind);
+    assert_eq!(monsters, world.monsters.len());
+    assert!(world.explored.len() >= explored);
+
+    world.update(world.next(KeyCode::Char('>')));
+    assert_eq!(second, world.player.pos);
+    assert_eq!(second_down, down(&world));
+
+    let again = map::generate_level(&world.dungeon, 2);
+    assert_eq!(second_down, down_of(&again.map));
+
+    fn down_of(map: &Map) -> Position {
+        map.stairs.iter().find(|(_, s)| **s == Stairs::Down).map(|(p, _)| *p).unwrap()
+    }
+}
+
+#[test]
+fn doors_open_and_close() {
+    let mut world = custom_world();
+    let d = world.map.areas[&Point { x: 25, y: 3 }];
+    let (door, beyond) = (Point { x: 27, y: 4 }, Point { x: 28, y: 4 });
+    world.player.pos = Point { x: 26, y: 4 };
+    world.player.area = d;
+    let sees = |world: &GameWorld, p| world.map.visible_tiles(world.player.pos).contains_key(&p);
+
+    assert!(!world.next(KeyCode::Right).takes_turn());
+    assert!(!world.next(KeyCode::Char('c')).takes_turn());
+    assert!(!sees(&world, beyond));
+
+    let next = world.next(KeyCode::Char('o'));
+    assert!(next.takes_turn());
+    world.update(next);
+    assert!(world.map.get_tile(door) == Some(TileKind::Door(Door::Open)));
+    assert!(sees(&world, beyond));
+    assert!(world.explored.contains_key(&beyond));
+
+    world.update(world.next(KeyCode::Right));
+    assert!(!world.next(KeyCode::Char('c')).takes_turn(), "the player is in the way");
+    world.update(world.next(KeyCode::Right));
+    assert_eq!(beyond, world.player.pos);
+
+    world.update(world.next(KeyCode::Char('c')));
+    assert!(world.map.get_tile(door) == Some(TileKind::Door(Door::Closed)));
+    assert!(!world.next(KeyCode::Left).takes_turn());
+    assert_eq!("You close the door.", world.messages().last().unwrap());
+    assert_ne!(char::from(Door::Open), char::from(Door::Closed));
+}
+
+#[test]
+fn secret_and_locked_doors() {
+    let mut world = custom_world();
+    let (door, beyond) = (Point { x: 27, y: 4 }, Point { x: 28, y: 4 });
+    let de = world.map.areas[&door];
+    world.map.set_door(de, Door::Secret);
+    world.player.pos = Point { x: 26, y: 4 };
+    world.player.area = world.map.areas[&world.player.pos];
+
+    assert!(world.map.get_tile(door).is_some_and(|t| t.is_wall()));
+    world.update(world.next(KeyCode::Char('o')));
+    assert!(world.map.get_tile(door).is_some_and(|t| t.is_wall()));
+    world.update(world.next(KeyCode::Char('s')));
+    assert!(world.map.get_tile(door) == Some(TileKind::Door(Door::Closed)));
+    assert_eq!("You find a secret door.", world.messages().last().unwrap());
+
+    world.map.set_door(de, Door::Locked);
+    let next = world.next(KeyCode::Char('o'));
+    assert!(!next.takes_turn());
+    world.update(next);
+    assert!(world.map.get_tile(door) == Some(TileKind::Door(Door::Locked)));
+    assert_eq!("The door is locked.", world.messages().last().unwrap());
+    world.player.inventory = vec![ItemKind::Dagger, ItemKind::Key];
+    world.update(world.next(KeyCode::Char('o')));
+    assert!(world.map.get_tile(door) == Some(TileKind::Door(Door::Open)));
+    assert_eq!([ItemKind::Dagger], world.inventory());
+
+    world.update(world.next(KeyCode::Right));
+    world.update(world.next(KeyCode::Right));
+    assert_eq!(beyond, world.player.pos);
+}
+
+#[test]
+fn halls_have_corners() {
+    let world = custom_world();
+    let hall = |x, y| match world.map.get_tile(Point { x, y }) {
+        Some(TileKind::Hall(dir)) => Some(dir),
+        _ => None,
+    };
+    assert!(hall(17, 5) == Some(Dir::Hor));
+    assert!(hall(18, 5) == Some(Dir::UR));
+    assert!(hall(18, 8) == Some(Dir::Ver));
+    assert!(hall(18, 12) == Some(Dir::DL));
+    assert!(hall(22, 12) == Some(Dir::Hor));
+}
+
+#[test]
+fn teleport_stays_on_this_side_of_locks() {
+    let mut world = (0..)
+        .map(|seed| generate_world(60, 30, 4, seed))
+        .find(|world| world.map.node_weights().any(|a| matches!(a, MapElem::Door(_, Door::Locked))))
+        .unwrap();
+    let near = world.map.reachable(world.player.area, |area| {
+        !matches!(area, MapElem::Door(_, Door::Locked))
+    });
+    assert!(near.len() < world.map.node_count());
+
+    for _ in 0..50 {
+        world.player.inventory = vec![ItemKind::TeleportScroll];
+        world.update(world.use_item(KeyCode::Char('a')));
+        assert!(near.contains(&world.player.area));
+    }
+}
+
+#[test]
+fn saves_check_every_level() {
+    let path = std::env::temp_dir().join("rogue_level_checks.save");
+    let load = |world: &GameWorld| {
+        world.save(0, &path).unwrap();
+        GameWorld::load(&path).map(|_| ())
+    };
+
+    let mut world = custom_world();
+    let room = world.player.pos + X(1);
+    world.map.stairs.insert(room, Stairs::Up);
+    assert_eq!(std::io::ErrorKind::InvalidData, load(&world).unwrap_err().kind());
+
+    let mut world = custom_world();
+    let down = *world.map.stairs.keys().next().unwrap();
+    world.player.pos = down;
+    world.player.area = world.map.areas[&down];
+    world.update(world.next(KeyCode::Char('>')));
+    assert!(load(&world).is_ok());
+    let level = world.levels.get_mut(&1).unwrap();
+    level.monsters.push(Monster::new(MonsterKind::Rat, room, NodeIndex::new(9999)));
+    assert_eq!(std::io::ErrorKind::InvalidData, load(&world).unwrap_err().kind());
+
+    let level = world.levels.get_mut(&1).unwrap();
+    level.monsters.clear();
+    level.items.push(Item { kind: ItemKind::Dagger, pos: Point { x: -5, y: -5 } });
+    assert_eq!(std::io::ErrorKind::InvalidData, load(&world).unwrap_err().kind());
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,76 @@
+use super::{GameWorld, Next, Change, MonsterKind, combat::Actor};
+
+/// Energy an actor needs before it can act.
+pub const ACTION_COST: i32 = 100;
+/// Energy the player gains every tick.
+pub const PLAYER_SPEED: i32 = 100;
+
+const MOVE_COST: i32 = ACTION_COST;
+const ATTACK_COST: i32 = ACTION_COST * 3 / 2;
+const WAIT_COST: i32 = ACTION_COST;
+const ITEM_COST: i32 = ACTION_COST;
+
+impl MonsterKind {
+    /// Energy the monster gains every tick.
+    pub fn speed(self) -> i32 {
+        match self {
+            MonsterKind::Rat => 150,
+            MonsterKind::Goblin => 100,
+            MonsterKind::Zombie => 50,
+        }
+    }
+}
+
+impl Next {
+    /// Who makes the change, if anyone.
+    pub fn actor(&self) -> Option<Actor> {
+        match self.0 {
+            Change::Pos(_) | Change::Area(..) |
+            Change::PickUp(_) | Change::Drop(_) | Change::Use(_) |
+            Change::Stairs(_) | Change::Door(..) => Some(Actor::Player),
+            Change::Monster(i, ..) => Some(Actor::Monster(i)),
+            Change::Attack(actor, ..) | Change::Wait(actor) => Some(actor),
+            Change::Nothing | Change::Init | Change::Locked => None,
+        }
+    }
+
+    /// Energy the change costs its actor.
+    pub fn cost(&self) -> i32 {
+        match self.0 {
+            Change::Pos(_) | Change::Area(..) | Change::Monster(..) |
+            Change::Stairs(_) | Change::Door(..) => MOVE_COST,
+            Change::PickUp(_) | Change::Drop(_) | Change::Use(_) => ITEM_COST,
+            Change::Attack(..) => ATTACK_COST,
+            Change::Wait(_) => WAIT_COST,
+            Change::Nothing | Change::Init | Change::Locked => 0,
+        }
+    }
+}
+
+impl GameWorld {
+    /// Whoever acts next. Actors gain energy by their speed until one of them
+    /// has enough to act. The player goes first, then the monsters in order.
+    pub fn next_actor(&mut self) -> Actor {
+        loop {
+            if self.player.energy >= ACTION_COST {
+                return Actor::Player;
+            }
+            if let Some(i) = self.monsters.iter().position(|m| m.energy >= ACTION_COST) {
+                return Actor::Monster(i);
+            }
+            self.player.energy += PLAYER_SPEED;
+            for monster in &mut self.monsters {
+                monster.energy += monster.kind.speed();
+            }
+        }
+    }
+
+    /// Takes the energy for `next` from its actor.
+    pub(super) fn spend(&mut self, next: Next) {
+        match next.actor() {
+            Some(Actor::Player) => self.player.energy -= next.cost(),
+            Some(Actor::Monster(i)) => self.monsters[i].energy -= next.cost(),
+            None => {}
+        }
+    }
+}
@@ -4,14 +4,33 @@ use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 
 pub fn input() -> Result<KeyCode> {
     loop {
-        if let Event::Key(key) = event::read()? {
-            if let KeyEventKind::Press = key.kind {
-                return Ok(key.code);
+        if let Input::Key(key) = input_event()? {
+            return Ok(key);
+        }
+    }
+}
+
+pub enum Input {
+    Key(KeyCode),
+    /// The terminal was resized to a new width and height.
+    Resize(u16, u16),
+}
+
+/// Waits for a key press or for the terminal to be resized.
+pub fn input_event() -> Result<Input> {
+    loop {
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                return Ok(Input::Key(key.code));
+            }
+            Event::Resize(width, height) => {
+                return Ok(Input::Resize(width, height));
             }
+            _ => {}
         }
     }
 }
 
-pub fn average(a: u16, b: u16) -> u16 {
+pub fn average(a: i32, b: i32) -> i32 {
     (a + b)/ 2
 }
@@ -1,89 +1,379 @@
-use std::io::{Write, Result};
+use std::io::Result;
 
-use crossterm::{queue,
-    cursor::{MoveTo, MoveToColumn},
-    style::{Print, SetForegroundColor, Color, ResetColor}};
+use crossterm::style::Color;
 
-use super::{GameWorld, Next, Dir, Tile, TileMap};
+use super::{GameWorld, Player, Next, Dir, Door, Stairs, Tile, TileMap, TileKind, ObjKind, MonsterKind, ItemDef,
+    Position, render::Renderer, points::{Point, Rect}, combat::{Actor, Stats}, item};
 
-const FADE_COLOUR: SetForegroundColor = SetForegroundColor(Color::DarkGrey);
+const FADE_COLOUR: Color = Color::DarkGrey;
+/// How close the player can get to the edge of the frame before it scrolls.
+const SCROLL_MARGIN: i32 = 4;
+/// Columns to the right of the frame, for the status panel.
+const HUD_WIDTH: u16 = 14;
+/// Messages shown in the log under the frame.
+const LOG_LINES: u16 = 3;
+/// The smallest screen the game can be drawn on.
+pub const MIN_SCREEN: (u16, u16) = (24 + HUD_WIDTH, 12 + LOG_LINES);
 
 impl GameWorld {
-    pub fn print(&self, out: &mut impl Write, next: Next) -> Result<()> {
+    pub fn print(&self, out: &mut impl Renderer, next: Next) -> Result<()> {
         use super::Change::*;
-        let Self { player, map } = self;
-        
-        draw_frame(0, 60, 0, 18, out)?;
+        let Self { player, map, explored, view, .. } = self;
+
+        view.draw_frame(out)?;
 
         match next.0 {
-            Nothing => return Ok(()),
+            Nothing | Wait(_) | PickUp(_) | Drop(_) | Use(_) | Stairs(_) | Door(..) | Locked => {
+                return Ok(());
+            }
             Init => {
-                map.visible_tiles(player.pos).draw(out)?;
+                view.clear(out)?;
+                explored.draw_faded(out, view)?;
+                self.visible_tiles(player.pos).draw(out, view)?;
             }
             Pos(p) | Area(p, _) => {
-                let new_vis = map.visible_tiles(p);
-                let old_vis = map.visible_tiles(player.pos);
-                
-                old_vis.clear_old(out, &new_vis)?;
-                new_vis.draw_new(out, &old_vis)?;
+                let new_vis = self.visible_tiles(p);
+                let new_view = view.follow(p);
+
+                if new_view != *view {
+                    new_view.clear(out)?;
+                    explored.draw_faded(out, &new_view)?;
+                    new_vis.draw(out, &new_view)?;
+                } else {
+                    let old_vis = self.visible_tiles(player.pos);
+
+                    map.visible_tiles(player.pos).clear_old(out, &new_vis, view)?;
+                    new_vis.draw_new(out, &old_vis, view)?;
+                }
+            }
+            Monster(i, p, _) => {
+                let vis = self.visible_tiles(player.pos);
+                let monster = &self.monsters[i];
+
+                out.colour(None)?;
+                if vis.contains_key(&monster.pos) {
+                    if let Some(kind) = self.floor_tile(monster.pos) {
+                        Tile { pos: monster.pos, kind }.draw(out, view)?;
+                    }
+                }
+                if vis.contains_key(&p) {
+                    let kind = TileKind::Obj(ObjKind::Monster(monster.kind));
+                    Tile { pos: p, kind }.draw(out, view)?;
+                }
             }
+            Attack(_, Actor::Monster(i), damage) => {
+                let monster = &self.monsters[i];
+                let vis = self.visible_tiles(player.pos);
+
+                if monster.stats.hp <= damage && vis.contains_key(&monster.pos) {
+                    if let Some(kind) = self.floor_tile(monster.pos) {
+                        out.colour(None)?;
+                        Tile { pos: monster.pos, kind }.draw(out, view)?;
+                    }
+                }
+            }
+            Attack(_, Actor::Player, _) => {}
         }
         out.flush()
     }
+
+    /// Shows how the game went, after the player has died.
+    pub fn print_results(&self, out: &mut impl Renderer) -> Result<()> {
+        out.clear()?;
+        out.colour(None)?;
+        out.print(2, 1, "You died.")?;
+        out.print(2, 3, &format!("Turns survived:  {}", self.turns))?;
+        out.print(2, 4, &format!("Monsters killed: {}", self.kills))?;
+        out.print(2, 5, &format!("Died on depth:   {}", self.depth))?;
+        out.print(2, 7, "Press any key to return to the menu.")?;
+        out.flush()
+    }
+
+    /// Lists the items the player carries under `title`.
+    pub fn print_inventory(&self, out: &mut impl Renderer, title: &str) -> Result<()> {
+        let inventory = self.inventory();
+        out.clear()?;
+        out.colour(None)?;
+        out.print(2, 1, &format!("{title} ({}/{})", inventory.len(), item::INVENTORY_SIZE))?;
+        for (row, (slot, kind)) in (3..).zip(self.equipment()) {
+            let name = kind.map_or("nothing", |kind| kind.name());
+            out.print(2, row, &format!("{slot:?}: {name}"))?;
+        }
+        if inventory.is_empty() {
+            out.print(2, 6, "You carry nothing.")?;
+        }
+        for (slot, kind) in inventory.iter().enumerate() {
+            let line = format!("{}) {}", item::slot_letter(slot), kind.name());
+            out.print(2, 6 + slot as u16, &line)?;
+        }
+        out.flush()
+    }
+
+    /// Shows the status panel to the right of the frame, and the newest
+    /// messages in the log under it.
+    pub fn print_hud(&self, out: &mut impl Renderer) -> Result<()> {
+        let Player { stats, character, .. } = &self.player;
+        let Stats { attack, defence, .. } = self.stats(Actor::Player);
+        let status = [
+            format!("HP    {}/{}", stats.hp.max(0), stats.max_hp),
+            format!("Level {}", character.level),
+            format!("Depth {}", self.depth()),
+            format!("Turn  {}", self.turns),
+            String::new(),
+            format!("XP    {}/{}", character.xp, character.next_level()),
+            format!("Str   {}", character.strength),
+            format!("Dex   {}", character.dexterity),
+            format!("Atk   {attack}"),
+            format!("Def   {defence}"),
+        ];
+
+        let Rect { pos, end } = self.view.frame;
+        let right = end.x + HUD_WIDTH;
+        draw_frame(end.x + 1, right, pos.y - 1, end.y, out)?;
+        draw_frame(pos.x - 1, right, end.y + 1, end.y + LOG_LINES + 2, out)?;
+
+        out.colour(None)?;
+        for (y, line) in (pos.y..end.y).zip(status) {
+            print_line(out, end.x + 2, y, HUD_WIDTH - 2, &line)?;
+        }
+        let shown = self.messages.len().saturating_sub(LOG_LINES.into());
+        let mut lines = self.messages[shown..].iter();
+        for y in (end.y + 2)..(end.y + 2 + LOG_LINES) {
+            let line = lines.next().map_or("", String::as_str);
+            print_line(out, pos.x, y, right - pos.x, line)?;
+        }
+        out.flush()
+    }
+
+    /// Shows the message history on the whole screen, `scroll` messages back
+    /// from the newest.
+    pub fn print_messages(
+        &self, out: &mut impl Renderer, width: u16, height: u16, scroll: usize,
+    ) -> Result<()> {
+        out.clear()?;
+        draw_frame(0, width - 1, 0, height - 1, out)?;
+        out.colour(None)?;
+        out.print(2, 0, " Messages ")?;
+
+        let rows = usize::from(height - 2);
+        let end = self.messages.len().saturating_sub(scroll);
+        let start = end.saturating_sub(rows);
+        for (y, line) in (1..).zip(&self.messages[start..end]) {
+            print_line(out, 1, y, width - 2, line)?;
+        }
+        out.flush()
+    }
+
+    /// Lays the frame out for a screen of `width` x `height`. The status
+    /// panel goes to the right of the frame and the log under it, with the
+    /// bottom row left free below them.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        let width = width.saturating_sub(HUD_WIDTH);
+        let height = height.saturating_sub(LOG_LINES + 2);
+        let frame = Rect::new(1, 1, width.saturating_sub(2), height.saturating_sub(3));
+        self.view = Viewport { frame, ..self.view }.follow(self.player.pos);
+    }
+
+    /// Draws the whole map, including tiles the player can't see.
+    pub fn print_map(&self, out: &mut impl Renderer) -> Result<()> {
+        let mut tiles = self.map.all_tiles();
+        tiles.insert(self.player.pos, TileKind::Obj(ObjKind::Player));
+
+        tiles.draw(out, &Viewport::screen())?;
+        out.flush()
+    }
+}
+
+/// Whether the game fits on a screen of `width` x `height`.
+pub fn fits(width: u16, height: u16) -> bool {
+    width >= MIN_SCREEN.0 && height >= MIN_SCREEN.1
+}
+
+/// Tells the player the screen is too small to draw the game on.
+pub fn print_too_small(out: &mut impl Renderer, width: u16, height: u16) -> Result<()> {
+    let (min_width, min_height) = MIN_SCREEN;
+    out.clear()?;
+    out.colour(None)?;
+    out.print(0, 0, "Terminal too small")?;
+    out.print(0, 1, &format!("{width}x{height} < {min_width}x{min_height}"))?;
+    out.flush()
+}
+
+/// The part of the world that is shown inside the frame.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Viewport {
+    /// The inside of the frame on the screen.
+    frame: Rect<u16>,
+    /// The world position shown in the top left corner of the frame.
+    camera: Position,
+} impl Viewport {
+    pub fn new(frame: Rect<u16>) -> Self {
+        let camera = Point { x: frame.pos.x.into(), y: frame.pos.y.into() };
+        Self { frame, camera }
+    }
+
+    /// Shows world positions at the same screen positions, without a frame.
+    fn screen() -> Self {
+        Self::new(Rect::new(0, 0, u16::MAX, u16::MAX))
+    }
+
+    /// Centres the camera on `p` if it is too close to the edge of the frame.
+    pub fn follow(self, p: Position) -> Self {
+        let Point { x: width, y: height } = self.size();
+        let margin = |size: i32| SCROLL_MARGIN.min((size - 1) / 2);
+        let Point { x, y } = p - self.camera;
+
+        let inside =
+            margin(width) <= x && x < width - margin(width) &&
+            margin(height) <= y && y < height - margin(height);
+        if inside {
+            return self;
+        }
+        Self { camera: p - self.size() / 2, ..self }
+    }
+
+    /// The screen position of a world position, if it is inside the frame.
+    pub fn to_screen(&self, p: Position) -> Option<(u16, u16)> {
+        let Point { x, y } = p - self.camera;
+        let Point { x: width, y: height } = self.size();
+        if x < 0 || y < 0 || x >= width || y >= height {
+            return None;
+        }
+        let pos = self.frame.pos;
+        Some((pos.x + x as u16, pos.y + y as u16))
+    }
+
+    fn size(&self) -> Position {
+        let Point { x, y } = self.frame.size();
+        Point { x: x.into(), y: y.into() }
+    }
+
+    fn clear(&self, out: &mut impl Renderer) -> Result<()> {
+        let Rect { pos, end } = self.frame;
+        for y in pos.y..end.y {
+            for x in pos.x..end.x {
+                out.put(x, y, ' ')?;
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_frame(&self, out: &mut impl Renderer) -> Result<()> {
+        let Rect { pos, end } = self.frame;
+        draw_frame(pos.x - 1, end.x, pos.y - 1, end.y, out)
+    }
+}
+impl Default for Viewport {
+    /// A 59 x 17 frame, until the game is laid out for the screen.
+    fn default() -> Self {
+        Self::new(Rect::new(1, 1, 59, 17))
+    }
 }
 
 impl TileMap {
-    fn draw(&self, out: &mut impl Write) -> Result<()> {
-        queue!(out, ResetColor)?;
+    fn draw(&self, out: &mut impl Renderer, view: &Viewport) -> Result<()> {
+        out.colour(None)?;
         for tile in self.tiles() {
-            tile.draw(out)?;
+            tile.draw(out, view)?;
         }
         Ok(())
     }
 
-    fn draw_new(&self, out: &mut impl Write, old: &TileMap) -> Result<()> {
-        queue!(out, ResetColor)?;
+    fn draw_new(
+        &self, out: &mut impl Renderer, old: &TileMap, view: &Viewport,
+    ) -> Result<()> {
+        out.colour(None)?;
         for tile in self.difference_player(old) {
-            tile.draw(out)?;
+            tile.draw(out, view)?;
         }
         Ok(())
     }
 
-    fn clear_old(&self, out: &mut impl Write, new: &TileMap) -> Result<()> {
-        queue!(out, FADE_COLOUR)?;
+    fn clear_old(
+        &self, out: &mut impl Renderer, new: &TileMap, view: &Viewport,
+    ) -> Result<()> {
+        out.colour(Some(FADE_COLOUR))?;
         for tile in self.difference(new) {
-            tile.clear(out)?;
+            tile.fade(out, view)?;
+        }
+        Ok(())
+    }
+
+    fn draw_faded(&self, out: &mut impl Renderer, view: &Viewport) -> Result<()> {
+        out.colour(Some(FADE_COLOUR))?;
+        for tile in self.tiles() {
+            tile.fade(out, view)?;
         }
         Ok(())
     }
 }
 
 impl Tile {
-    fn draw(&self, out: &mut impl Write) -> Result<()> {
+    fn draw(&self, out: &mut impl Renderer, view: &Viewport) -> Result<()> {
         use super::{TileKind::*, ObjKind::*};
-        queue!(out, MoveTo(self.pos.x, self.pos.y))?;
+        let Some((x, y)) = view.to_screen(self.pos) else { return Ok(()) };
         match self.kind {
-            Door => queue!(out, Print('∏')),
-            Room => queue!(out, Print('.')),
-            Hall(_) => queue!(out, Print('░')),
-            Wall(dir) => queue!(out, Print(char::from(dir))),
-            Obj(Player) => queue!(out, Print('@')),
+            Door(door) => out.put(x, y, door.into()),
+            Room => out.put(x, y, '.'),
+            Hall(_) => out.put(x, y, '░'),
+            Wall(dir) => out.put(x, y, dir.into()),
+            Stairs(stairs) => out.put(x, y, stairs.into()),
+            Obj(Player) => out.put(x, y, '@'),
+            Obj(Monster(kind)) => out.put(x, y, kind.into()),
+            Obj(Item(kind)) => {
+                let ItemDef { glyph, colour, .. } = kind.def();
+                out.colour(Some(colour))?;
+                out.put(x, y, glyph)?;
+                out.colour(None)
+            }
         }
     }
 
-    fn clear(&self, out: &mut impl Write) -> Result<()> {
+    /// Draws a tile the player remembers, but can't see.
+    fn fade(&self, out: &mut impl Renderer, view: &Viewport) -> Result<()> {
         use super::TileKind::*;
-        queue!(out, MoveTo(self.pos.x, self.pos.y))?;
+        let Some((x, y)) = view.to_screen(self.pos) else { return Ok(()) };
         match self.kind {
-            Door => queue!(out, Print('∏')),
-            Room => queue!(out, Print(' ')),
-            Hall(_) => queue!(out, Print('░')),
-            Wall(dir) => queue!(out, Print(char::from(dir))),
+            Door(door) => out.put(x, y, door.into()),
+            Room => out.put(x, y, '.'),
+            Hall(_) => out.put(x, y, '░'),
+            Wall(dir) => out.put(x, y, dir.into()),
+            Stairs(stairs) => out.put(x, y, stairs.into()),
             _ => Ok(()),
         }
     }
 }
 
+impl From<MonsterKind> for char {
+    fn from(kind: MonsterKind) -> Self {
+        match kind {
+            MonsterKind::Rat => 'r',
+            MonsterKind::Goblin => 'g',
+            MonsterKind::Zombie => 'z',
+        }
+    }
+}
+
+/// Locked doors look like any other closed door. Secret doors aren't drawn
+/// as doors at all, but as the wall they are in.
+impl From<Door> for char {
+    fn from(door: Door) -> Self {
+        match door {
+            Door::Open => '\'',
+            Door::Closed | Door::Locked | Door::Secret => '∏',
+        }
+    }
+}
+
+impl From<Stairs> for char {
+    fn from(stairs: Stairs) -> Self {
+        match stairs {
+            Stairs::Up => '<',
+            Stairs::Down => '>',
+        }
+    }
+}
+
 impl From<Dir> for char {
     fn from(dir: Dir) -> Self { use super::Dir::*;
         match dir { //None => '■',
@@ -95,29 +385,35 @@ impl From<Dir> for char {
     }
 }
 
-fn draw_frame(
+/// Prints `text` cut or padded to `width`, so it covers what was there.
+fn print_line(out: &mut impl Renderer, x: u16, y: u16, width: u16, text: &str) -> Result<()> {
+    let line: String = text.chars().chain(std::iter::repeat(' ')).take(width.into()).collect();
+    out.print(x, y, &line)
+}
+
+pub(super) fn draw_frame(
     left: u16, right: u16, top: u16, bottom: u16,
-    out: &mut impl Write,
+    out: &mut impl Renderer,
 ) -> Result<()> {
     
-    queue!(out, FADE_COLOUR)?;
+    out.colour(Some(FADE_COLOUR))?;
 
-    queue!(out, MoveTo(left, top), Print('╔'))?;
-    for _ in (left + 1)..right {
-        queue!(out, Print('═'))?;
+    out.put(left, top, '╔')?;
+    for x in (left + 1)..right {
+        out.put(x, top, '═')?;
     }
-    queue!(out, Print('╗'))?;
+    out.put(right, top, '╗')?;
 
     for row in (top + 1)..bottom {
-        queue!(out, MoveTo(left, row), Print('║'))?;
-        queue!(out, MoveToColumn(right), Print('║'))?;
+        out.put(left, row, '║')?;
+        out.put(right, row, '║')?;
     }
 
-    queue!(out, MoveTo(left, bottom), Print('╚'))?;
-    for _ in (left + 1)..right {
-        queue!(out, Print('═'))?;
+    out.put(left, bottom, '╚')?;
+    for x in (left + 1)..right {
+        out.put(x, bottom, '═')?;
     }
-    queue!(out, Print('╝'))?;
+    out.put(right, bottom, '╝')?;
 
     Ok(())
 }